@@ -1,145 +1,208 @@
-#![feature(test)]
-extern crate test;
 extern crate maze;
 
+use std::env;
+use std::time::{Duration, Instant};
+
+// a minimal stand-in for the unstable test::Bencher, runs the closure for about a second
+pub struct Bencher {
+	name: &'static str
+}
+
+impl Bencher {
+	pub fn iter<T, F: FnMut() -> T>(&mut self, mut f: F) {
+		let mut iterations = 0u32;
+		let start = Instant::now();
+
+		while iterations == 0 || start.elapsed() < Duration::from_secs(1) {
+			f();
+			iterations += 1;
+		}
+
+		println!("test {} ... bench: {:>14} ns/iter",
+			self.name, (start.elapsed() / iterations).as_nanos());
+	}
+}
+
 macro_rules! bench_build_maze {
 	($b:expr, $width:expr, $height:expr, $gen:expr) => {
 		$b.iter(|| {
-			let _ = MazeBuilder::new()
+			MazeBuilder::new()
 					.width($width)
 					.height($height)
 					.generate_using($gen)
-					.build::<InMemoryPackedGrid>(&[]);
+					.build::<InMemoryPackedGrid>(&[])
+					.unwrap()
 		})
 	};
 	($b:expr, $width:expr, $height:expr, $gen:expr, $mem:ty, $options:expr) => {
 		$b.iter(|| {
-			let _ = MazeBuilder::new()
+			MazeBuilder::new()
 					.width($width)
 					.height($height)
 					.generate_using($gen)
-					.build::<$mem>($options);
+					.build::<$mem>($options)
+					.unwrap()
 		})
 	}
 }
 
+macro_rules! benches {
+	($($name:ident),*) => {
+		pub fn main() {
+			// the first free argument filters the benchmarks by name, like libtest does
+			let filter = env::args().skip(1).find(|arg| !arg.starts_with("--"));
+
+			$(
+				if filter.as_ref().map_or(true, |f| stringify!($name).contains(f.as_str())) {
+					benches::$name(&mut Bencher { name: stringify!($name) });
+				}
+			)*
+		}
+	}
+}
+
+benches!(
+	memory_32_32_sidewinder,
+	memory_32_32_parallel_sidewinder,
+	memory_32_32_recursive_division,
+	memory_32_32_stack_division,
+	memory_32_32_stack_backtrack,
+	memory_32_32_binary_tree,
+	memory_1024_1024_sidewinder,
+	memory_1024_1024_parallel_sidewinder,
+	memory_1024_1024_recursive_division,
+	memory_1024_1024_stack_division,
+	memory_1024_1024_stack_backtrack,
+	memory_1024_1024_binary_tree,
+	mmap_32_32_sidewinder,
+	mmap_32_32_parallel_sidewinder,
+	mmap_32_32_recursive_division,
+	mmap_32_32_stack_division,
+	mmap_32_32_stack_backtrack,
+	mmap_32_32_binary_tree,
+	mmap_1024_1024_sidewinder,
+	mmap_1024_1024_parallel_sidewinder,
+	mmap_1024_1024_recursive_division,
+	mmap_1024_1024_stack_division,
+	mmap_1024_1024_stack_backtrack,
+	mmap_1024_1024_binary_tree
+);
+
 mod benches {
 	use maze::*;
-	use test::Bencher;
+	use super::Bencher;
 
-	#[bench]
-	fn memory_32_32_sidewinder(b: &mut Bencher) {
+	pub fn memory_32_32_sidewinder(b: &mut Bencher) {
 		bench_build_maze!(b, 32, 32, GeneratorType::Sidewinder);
 	}
 
-	#[bench]
-	fn memory_32_32_parallel_sidewinder(b: &mut Bencher) {
+	pub fn memory_32_32_parallel_sidewinder(b: &mut Bencher) {
 		bench_build_maze!(b, 32, 32, GeneratorType::ParallelSidewinder);
 	}
 
-	#[bench]
-	fn memory_32_32_recursive_division(b: &mut Bencher) {
+	pub fn memory_32_32_recursive_division(b: &mut Bencher) {
 		bench_build_maze!(b, 32, 32, GeneratorType::RecursiveDivision);
 	}
 
-	#[bench]
-	fn memory_32_32_stack_division(b: &mut Bencher) {
+	pub fn memory_32_32_stack_division(b: &mut Bencher) {
 		bench_build_maze!(b, 32, 32, GeneratorType::StackDivision);
 	}
 
-	#[bench]
-	fn memory_32_32_stack_backtrack(b: &mut Bencher) {
+	pub fn memory_32_32_stack_backtrack(b: &mut Bencher) {
 		bench_build_maze!(b, 32, 32, GeneratorType::StackBacktrack);
 	}
 
+	pub fn memory_32_32_binary_tree(b: &mut Bencher) {
+		bench_build_maze!(b, 32, 32, GeneratorType::BinaryTree);
+	}
+
 
 
-	#[bench]
-	fn memory_1024_1024_sidewinder(b: &mut Bencher) {
+	pub fn memory_1024_1024_sidewinder(b: &mut Bencher) {
 		bench_build_maze!(b, 1024, 1024, GeneratorType::Sidewinder);
 	}
 
-	#[bench]
-	fn memory_1024_1024_parallel_sidewinder(b: &mut Bencher) {
+	pub fn memory_1024_1024_parallel_sidewinder(b: &mut Bencher) {
 		bench_build_maze!(b, 1024, 1024, GeneratorType::ParallelSidewinder);
 	}
 
-	#[bench]
-	fn memory_1024_1024_recursive_division(b: &mut Bencher) {
+	pub fn memory_1024_1024_recursive_division(b: &mut Bencher) {
 		bench_build_maze!(b, 1024, 1024, GeneratorType::RecursiveDivision);
 	}
 
-	#[bench]
-	fn memory_1024_1024_stack_division(b: &mut Bencher) {
+	pub fn memory_1024_1024_stack_division(b: &mut Bencher) {
 		bench_build_maze!(b, 1024, 1024, GeneratorType::StackDivision);
 	}
 
-	#[bench]
-	fn memory_1024_1024_stack_backtrack(b: &mut Bencher) {
+	pub fn memory_1024_1024_stack_backtrack(b: &mut Bencher) {
 		bench_build_maze!(b, 1024, 1024, GeneratorType::StackBacktrack);
 	}
 
+	pub fn memory_1024_1024_binary_tree(b: &mut Bencher) {
+		bench_build_maze!(b, 1024, 1024, GeneratorType::BinaryTree);
+	}
+
 
 
-	#[bench]
-	fn mmap_32_32_sidewinder(b: &mut Bencher) {
+	pub fn mmap_32_32_sidewinder(b: &mut Bencher) {
 		bench_build_maze!(b, 32, 32, GeneratorType::Sidewinder, MMAPPackedGrid,
 			&[PackedOption::MMAPFilePath(format!("maze_{}x{}.bin", 32, 32))]);
 	}
 
-	#[bench]
-	fn mmap_32_32_parallel_sidewinder(b: &mut Bencher) {
+	pub fn mmap_32_32_parallel_sidewinder(b: &mut Bencher) {
 		bench_build_maze!(b, 32, 32, GeneratorType::ParallelSidewinder, MMAPPackedGrid,
 			&[PackedOption::MMAPFilePath(format!("maze_{}x{}.bin", 32, 32))]);
 	}
 
-	#[bench]
-	fn mmap_32_32_recursive_division(b: &mut Bencher) {
+	pub fn mmap_32_32_recursive_division(b: &mut Bencher) {
 		bench_build_maze!(b, 32, 32, GeneratorType::RecursiveDivision, MMAPPackedGrid,
 			&[PackedOption::MMAPFilePath(format!("maze_{}x{}.bin", 32, 32))]);
 	}
 
-	#[bench]
-	fn mmap_32_32_stack_division(b: &mut Bencher) {
+	pub fn mmap_32_32_stack_division(b: &mut Bencher) {
 		bench_build_maze!(b, 32, 32, GeneratorType::StackDivision, MMAPPackedGrid,
 			&[PackedOption::MMAPFilePath(format!("maze_{}x{}.bin", 32, 32))]);
 	}
 
-	#[bench]
-	fn mmap_32_32_stack_backtrack(b: &mut Bencher) {
+	pub fn mmap_32_32_stack_backtrack(b: &mut Bencher) {
 		bench_build_maze!(b, 32, 32, GeneratorType::StackBacktrack, MMAPPackedGrid,
 			&[PackedOption::MMAPFilePath(format!("maze_{}x{}.bin", 32, 32))]);
 	}
 
+	pub fn mmap_32_32_binary_tree(b: &mut Bencher) {
+		bench_build_maze!(b, 32, 32, GeneratorType::BinaryTree, MMAPPackedGrid,
+			&[PackedOption::MMAPFilePath(format!("maze_{}x{}.bin", 32, 32))]);
+	}
+
 
 
-	#[bench]
-	fn mmap_1024_1024_sidewinder(b: &mut Bencher) {
+	pub fn mmap_1024_1024_sidewinder(b: &mut Bencher) {
 		bench_build_maze!(b, 1024, 1024, GeneratorType::Sidewinder, MMAPPackedGrid,
 			&[PackedOption::MMAPFilePath(format!("maze_{}x{}.bin", 1024, 1024))]);
 	}
 
-	#[bench]
-	fn mmap_1024_1024_parallel_sidewinder(b: &mut Bencher) {
+	pub fn mmap_1024_1024_parallel_sidewinder(b: &mut Bencher) {
 		bench_build_maze!(b, 1024, 1024, GeneratorType::ParallelSidewinder, MMAPPackedGrid,
 			&[PackedOption::MMAPFilePath(format!("maze_{}x{}.bin", 1024, 1024))]);
 	}
 
-	#[bench]
-	fn mmap_1024_1024_recursive_division(b: &mut Bencher) {
+	pub fn mmap_1024_1024_recursive_division(b: &mut Bencher) {
 		bench_build_maze!(b, 1024, 1024, GeneratorType::RecursiveDivision, MMAPPackedGrid,
 			&[PackedOption::MMAPFilePath(format!("maze_{}x{}.bin", 1024, 1024))]);
 	}
 
-	#[bench]
-	fn mmap_1024_1024_stack_division(b: &mut Bencher) {
+	pub fn mmap_1024_1024_stack_division(b: &mut Bencher) {
 		bench_build_maze!(b, 1024, 1024, GeneratorType::StackDivision, MMAPPackedGrid,
 			&[PackedOption::MMAPFilePath(format!("maze_{}x{}.bin", 1024, 1024))]);
 	}
 
-	#[bench]
-	fn mmap_1024_1024_stack_backtrack(b: &mut Bencher) {
+	pub fn mmap_1024_1024_stack_backtrack(b: &mut Bencher) {
 		bench_build_maze!(b, 1024, 1024, GeneratorType::StackBacktrack, MMAPPackedGrid,
 			&[PackedOption::MMAPFilePath(format!("maze_{}x{}.bin", 1024, 1024))]);
 	}
+
+	pub fn mmap_1024_1024_binary_tree(b: &mut Bencher) {
+		bench_build_maze!(b, 1024, 1024, GeneratorType::BinaryTree, MMAPPackedGrid,
+			&[PackedOption::MMAPFilePath(format!("maze_{}x{}.bin", 1024, 1024))]);
+	}
 }
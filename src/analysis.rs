@@ -0,0 +1,182 @@
+use std::collections::VecDeque;
+use std::fmt;
+use ::{Grid, SolverSolution};
+use ::bitset::BitSet;
+use ::utils::Direction;
+
+#[derive(Clone, Debug)]
+pub struct Stats {
+	pub width: u64,
+	pub height: u64,
+
+	// cells with a single passage
+	pub dead_ends: u64,
+	// cells with exactly two passages
+	pub corridors: u64,
+	// cells with three or four passages
+	pub junctions: u64,
+	pub solution_length: Option<u64>,
+	// the longest shortest path from the region of the top left cell, exact for perfect mazes
+	pub diameter: u64,
+	// average number of steps between two cells that aren't corridors
+	pub average_corridor_length: f64,
+	// average number of steps from a dead end along its corridor to the first cell that isn't a
+	// corridor, usually a junction, 0 without dead ends, mazes that "flow" have few but long
+	// dead ends and a high river
+	pub river: f64
+}
+
+impl Stats {
+	pub fn to_json(&self) -> String {
+		format!(concat!("{{\"width\":{},\"height\":{},\"dead_ends\":{},\"corridors\":{},",
+				"\"junctions\":{},\"solution_length\":{},\"diameter\":{},",
+				"\"average_corridor_length\":{},\"river\":{}}}"),
+			self.width, self.height, self.dead_ends, self.corridors,
+			self.junctions, self.solution_length.map_or(String::from("null"), |l| l.to_string()),
+			self.diameter, self.average_corridor_length, self.river)
+	}
+}
+
+impl fmt::Display for Stats {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		writeln!(f, "size: {}x{}", self.width, self.height)?;
+		writeln!(f, "dead ends: {}", self.dead_ends)?;
+		writeln!(f, "corridors: {}", self.corridors)?;
+		writeln!(f, "junctions: {}", self.junctions)?;
+		match self.solution_length {
+			Some(length) => writeln!(f, "solution length: {}", length)?,
+			None => writeln!(f, "solution length: no solution")?
+		}
+		writeln!(f, "diameter: {}", self.diameter)?;
+		writeln!(f, "average corridor length: {:.3}", self.average_corridor_length)?;
+		write!(f, "river: {:.3}", self.river)
+	}
+}
+
+pub fn analyze<G: Grid>(grid: &G, solution: Option<&SolverSolution>) -> Stats {
+	let (width, height) = (grid.width(), grid.height());
+
+	let mut dead_ends = 0;
+	let mut corridors = 0;
+	let mut junctions = 0;
+	// every passage is counted from both of its ends
+	let mut passage_ends = 0;
+	let mut corridor_ends = 0;
+	let mut dead_end_steps = 0;
+
+	for y in 0..height {
+		for x in 0..width {
+			let degree = neighbours(grid, x, y).len() as u64;
+
+			passage_ends += degree;
+
+			match degree {
+				0 => {},
+				1 => {
+					dead_ends += 1;
+					dead_end_steps += dead_end_length(grid, (x, y));
+				},
+				2 => corridors += 1,
+				_ => junctions += 1
+			}
+
+			if degree != 2 {
+				corridor_ends += degree;
+			}
+		}
+	}
+
+	let passages = passage_ends / 2;
+
+	// corridors run between the cells that aren't corridors themselves
+	let average_corridor_length = if corridor_ends == 0 {
+		passages as f64
+	} else {
+		passages as f64 / (corridor_ends as f64 / 2.0)
+	};
+
+	let (farthest, _) = farthest_from(grid, (0, 0));
+	let (_, diameter) = farthest_from(grid, farthest);
+
+	Stats {
+		width,
+		height,
+		dead_ends,
+		corridors,
+		junctions,
+		solution_length: solution.map(|s| s.len() as u64),
+		diameter,
+		average_corridor_length,
+		river: if dead_ends == 0 { 0.0 } else { dead_end_steps as f64 / dead_ends as f64 }
+	}
+}
+
+// the cells reachable in one step, passages leading out of the grid are ignored
+fn neighbours<G: Grid>(grid: &G, x: u64, y: u64) -> Vec<(u64, u64)> {
+	use ::utils::Direction::*;
+
+	let mut cells = Vec::with_capacity(4);
+
+	for &dir in Direction::enumerate() {
+		if grid.test(x, y, dir as u8) {
+			let (nx, ny) = match dir {
+				S => (x, y + 1),
+				E => (x + 1, y),
+				N => (x, y - 1),
+				W => (x - 1, y)
+			};
+
+			if nx < grid.width() && ny < grid.height() {
+				cells.push((nx, ny));
+			}
+		}
+	}
+
+	cells
+}
+
+// the number of steps from a dead end to the first cell that isn't a corridor
+fn dead_end_length<G: Grid>(grid: &G, dead_end: (u64, u64)) -> u64 {
+	let mut previous = dead_end;
+	let mut cell = neighbours(grid, dead_end.0, dead_end.1)[0];
+	let mut steps = 1;
+
+	loop {
+		let next = neighbours(grid, cell.0, cell.1);
+
+		if next.len() != 2 {
+			return steps;
+		}
+
+		let following = if next[0] == previous { next[1] } else { next[0] };
+
+		previous = cell;
+		cell = following;
+		steps += 1;
+	}
+}
+
+// breadth-first search returning the last cell reached and its distance
+fn farthest_from<G: Grid>(grid: &G, start: (u64, u64)) -> ((u64, u64), u64) {
+	let width = grid.width();
+
+	let mut visited = BitSet::new(width * grid.height());
+	let mut queue = VecDeque::new();
+	let mut farthest = (start, 0);
+
+	visited.insert(start.1 * width + start.0);
+	queue.push_back((start, 0));
+
+	while let Some(((x, y), distance)) = queue.pop_front() {
+		farthest = ((x, y), distance);
+
+		for (nx, ny) in neighbours(grid, x, y) {
+			if !visited.contains(ny * width + nx) {
+				visited.insert(ny * width + nx);
+				queue.push_back(((nx, ny), distance + 1));
+			}
+		}
+	}
+
+	farthest
+}
@@ -0,0 +1,21 @@
+pub struct BitSet {
+	words: Vec<u64>
+}
+
+impl BitSet {
+	pub fn new(len: u64) -> Self {
+		BitSet {
+			words: vec![0; len.div_ceil(64) as usize]
+		}
+	}
+
+	#[inline]
+	pub fn contains(&self, index: u64) -> bool {
+		self.words[(index / 64) as usize] & (1 << (index % 64)) != 0
+	}
+
+	#[inline]
+	pub fn insert(&mut self, index: u64) {
+		self.words[(index / 64) as usize] |= 1 << (index % 64);
+	}
+}
@@ -0,0 +1,45 @@
+// CRC-32 as used by PNG, zlib and friends (reflected, polynomial 0xEDB88320)
+pub struct Crc32 {
+	value: u32
+}
+
+// built once at compile time rather than for every checksum
+static TABLE: [u32; 256] = table();
+
+const fn table() -> [u32; 256] {
+	let mut table = [0u32; 256];
+	let mut n = 0;
+
+	while n < 256 {
+		let mut c = n as u32;
+		let mut k = 0;
+
+		while k < 8 {
+			c = if c & 1 != 0 { 0xEDB88320 ^ (c >> 1) } else { c >> 1 };
+			k += 1;
+		}
+
+		table[n] = c;
+		n += 1;
+	}
+
+	table
+}
+
+impl Crc32 {
+	pub fn new() -> Self {
+		Crc32 {
+			value: 0xFFFFFFFF
+		}
+	}
+
+	pub fn update(&mut self, data: &[u8]) {
+		for &byte in data {
+			self.value = TABLE[((self.value ^ byte as u32) & 0xFF) as usize] ^ (self.value >> 8);
+		}
+	}
+
+	pub fn finish(&self) -> u32 {
+		self.value ^ 0xFFFFFFFF
+	}
+}
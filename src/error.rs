@@ -0,0 +1,46 @@
+use std::error;
+use std::fmt;
+use std::io;
+use std::result;
+
+#[derive(Debug)]
+pub enum Error {
+	Io(io::Error),
+	// the grid can't have the given width and height
+	InvalidDimensions(u64, u64),
+	// the file is not a maze file, has an unsupported version or is corrupted
+	InvalidFormat(String),
+	InvalidArgument(String),
+	// the generation was cancelled through its progress hook
+	Cancelled
+}
+
+pub type Result<T> = result::Result<T, Error>;
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Error::Io(ref e) => write!(f, "I/O error: {}", e),
+			Error::InvalidDimensions(width, height) =>
+				write!(f, "invalid grid dimensions {}x{}", width, height),
+			Error::InvalidFormat(ref message) => write!(f, "invalid maze file: {}", message),
+			Error::InvalidArgument(ref message) => write!(f, "invalid argument: {}", message),
+			Error::Cancelled => write!(f, "the generation was cancelled")
+		}
+	}
+}
+
+impl error::Error for Error {
+	fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+		match *self {
+			Error::Io(ref e) => Some(e),
+			_ => None
+		}
+	}
+}
+
+impl From<io::Error> for Error {
+	fn from(e: io::Error) -> Error {
+		Error::Io(e)
+	}
+}
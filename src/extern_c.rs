@@ -1,76 +1,276 @@
 extern crate libc;
 
-use ::{GeneratorType, generate, Grid};
+use ::{GeneratorType, GeneratorOption, generate, Grid, Error, Result};
+use ::generator::{Progress, Seed};
 use ::grid::mmap_packed_grid::MMAPPackedGrid;
 use self::libc::*;
-use std::mem::transmute;
-use std::ffi::CStr;
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
 use std::fs::OpenOptions;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+pub const MAZE_OK: c_int = 0;
+pub const MAZE_ERROR_IO: c_int = -1;
+pub const MAZE_ERROR_INVALID_DIMENSIONS: c_int = -2;
+pub const MAZE_ERROR_INVALID_FORMAT: c_int = -3;
+pub const MAZE_ERROR_INVALID_ARGUMENT: c_int = -4;
+pub const MAZE_CANCELLED: c_int = -5;
+// a bug in the library, the message tells where
+pub const MAZE_ERROR_PANIC: c_int = -6;
+
+// returned by maze_get instead of a cell, cells only ever take up 2 bits
+pub const MAZE_INVALID_CELL: u8 = 0xFF;
+
+thread_local!(static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) });
+
+fn set_last_error(error: Error) -> c_int {
+	let status = match error {
+		Error::Io(_) => MAZE_ERROR_IO,
+		Error::InvalidDimensions(..) => MAZE_ERROR_INVALID_DIMENSIONS,
+		Error::InvalidFormat(_) => MAZE_ERROR_INVALID_FORMAT,
+		Error::InvalidArgument(_) => MAZE_ERROR_INVALID_ARGUMENT,
+		Error::Cancelled => MAZE_CANCELLED
+	};
+
+	set_last_message(status, &format!("{}", error))
+}
+
+fn set_last_message(status: c_int, message: &str) -> c_int {
+	let message = CString::new(message.replace("\0", "")).unwrap();
+
+	LAST_ERROR.with(|last_error| *last_error.borrow_mut() = Some(message));
+
+	status
+}
+
+// runs the body of an exported function, errors are recorded for maze_last_error and turned
+// into their status and a panic is caught rather than unwinding into the caller
+fn guard<T, F: FnOnce() -> Result<T>>(body: F) -> ::std::result::Result<T, c_int> {
+	match panic::catch_unwind(AssertUnwindSafe(body)) {
+		Ok(Ok(value)) => Ok(value),
+		Ok(Err(e)) => Err(set_last_error(e)),
+		Err(payload) => {
+			let message = match payload.downcast_ref::<&str>() {
+				Some(message) => message.to_string(),
+				None => payload.downcast_ref::<String>().cloned()
+					.unwrap_or_else(|| String::from("unknown cause"))
+			};
+
+			Err(set_last_message(MAZE_ERROR_PANIC, &format!("panicked: {}", message)))
+		}
+	}
+}
+
+fn status(result: ::std::result::Result<(), c_int>) -> c_int {
+	match result {
+		Ok(()) => MAZE_OK,
+		Err(status) => status
+	}
+}
+
+// generator types are passed as their id, any other value is rejected
+fn generator_type_from_id(id: u32) -> Result<GeneratorType> {
+	GeneratorType::from_id(id)
+		.ok_or_else(|| Error::InvalidArgument(format!("unknown generator type {}", id)))
+}
+
+unsafe fn path_from_ptr(filepath: *const c_char) -> Result<String> {
+	if filepath.is_null() {
+		return Err(Error::InvalidArgument(String::from("file path is null")));
+	}
+
+	CStr::from_ptr(filepath).to_str()
+		.map(String::from)
+		.map_err(|_| Error::InvalidArgument(String::from("file path is not valid UTF-8")))
+}
+
+unsafe fn maze_from_ptr<'a>(maze: *mut c_void) -> Result<&'a mut MMAPPackedGrid> {
+	if maze.is_null() {
+		Err(Error::InvalidArgument(String::from("maze is null")))
+	} else {
+		Ok(&mut *(maze as *mut MMAPPackedGrid))
+	}
+}
+
+fn into_raw(maze: ::std::result::Result<MMAPPackedGrid, c_int>) -> *mut c_void {
+	match maze {
+		Ok(maze) => Box::into_raw(Box::new(maze)) as *mut c_void,
+		Err(_) => ptr::null_mut()
+	}
+}
+
+// the message of the last error on the calling thread, or null if there was none,
+// valid until the next failing call on the same thread
+#[no_mangle]
+pub extern "C"
+fn maze_last_error() -> *const c_char {
+	guard(|| Ok(LAST_ERROR.with(|last_error| match *last_error.borrow() {
+		Some(ref message) => message.as_ptr(),
+		None => ptr::null()
+	}))).unwrap_or(ptr::null())
+}
 
 #[no_mangle]
+/// # Safety
+///
+/// `filepath` must be null or point to a nul terminated string.
 pub unsafe extern "C"
-fn maze_create(filepath: *const c_char, width: uint64_t, height: uint64_t) -> *mut c_void {
+fn maze_create(filepath: *const c_char, width: u64, height: u64) -> *mut c_void {
 	use PackedOption::*;
 
-	let maze = Box::new(MMAPPackedGrid::new(&[
-		MMAPFilePath(String::from(CStr::from_ptr(filepath).to_str().unwrap())),
+	into_raw(guard(|| MMAPPackedGrid::new(&[
+		MMAPFilePath(path_from_ptr(filepath)?),
 		Width(width),
 		Height(height)
-	]));
-
-	transmute(Box::into_raw(maze))
+	])))
 }
 
 #[no_mangle]
+/// # Safety
+///
+/// `filepath` must be null or point to a nul terminated string.
 pub unsafe extern "C"
 fn maze_open(filepath: *const c_char) -> *mut c_void {
-	let filepath_str = CStr::from_ptr(filepath).to_str().unwrap();
-
-	let maze = Box::new(
-			MMAPPackedGrid::from_file(
-				OpenOptions::new().read(true).write(true).open(&filepath_str).unwrap()
-			)
-		);
+	into_raw(guard(|| {
+		let file = OpenOptions::new().read(true).write(true).open(path_from_ptr(filepath)?)?;
 
-	transmute(Box::into_raw(maze))
+		MMAPPackedGrid::from_file(file)
+	}))
 }
 
 #[no_mangle]
+/// # Safety
+///
+/// `maze` must be null or a maze returned by `maze_create` or `maze_open` that wasn't freed
+/// yet and isn't used by another thread at the same time.
 pub unsafe extern "C"
 fn maze_free(maze: *mut c_void) {
-	let _: Box<MMAPPackedGrid> = Box::from_raw(transmute(maze));
+	let _ = guard(|| {
+		if !maze.is_null() {
+			let _: Box<MMAPPackedGrid> = Box::from_raw(maze as *mut MMAPPackedGrid);
+
+			// NOTE: the box was dropped above
+		}
+
+		Ok(())
+	});
+}
+
+#[no_mangle]
+/// # Safety
+///
+/// `maze` must be null or a maze returned by `maze_create` or `maze_open` that wasn't freed
+/// yet and isn't used by another thread at the same time.
+pub unsafe extern "C"
+fn maze_get(maze: *mut c_void, x: u64, y: u64) -> u8 {
+	guard(|| {
+		let maze = maze_from_ptr(maze)?;
 
-	// NOTE: the box was dropped above
+		if x < maze.width() && y < maze.height() {
+			Ok(maze.get_unchecked(x, y))
+		} else {
+			Err(Error::InvalidArgument(format!("cell {},{} is outside of the {}x{} maze",
+				x, y, maze.width(), maze.height())))
+		}
+	}).unwrap_or(MAZE_INVALID_CELL)
 }
 
 #[no_mangle]
+/// # Safety
+///
+/// `maze` must be null or a maze returned by `maze_create` or `maze_open` that wasn't freed
+/// yet and isn't used by another thread at the same time.
 pub unsafe extern "C"
-fn maze_get(maze: *mut c_void, x: uint64_t, y: uint64_t) -> uint8_t {
-	let maze: &mut MMAPPackedGrid = transmute(maze);
+fn maze_generate(maze: *mut c_void, generator_type: u32) -> c_int {
+	status(guard(|| {
+		let maze = maze_from_ptr(maze)?;
+		let generator_type = generator_type_from_id(generator_type)?;
+
+		generate(maze, generator_type, &[])?;
+		maze.set_generator_info(generator_type, None);
 
-	maze.get(x, y)
+		Ok(())
+	}))
 }
 
 #[no_mangle]
+/// # Safety
+///
+/// `maze` must be null or a maze returned by `maze_create` or `maze_open` that wasn't freed
+/// yet and isn't used by another thread at the same time.
 pub unsafe extern "C"
-fn maze_generate(maze: *mut c_void, generator_type: GeneratorType) {
-	let maze: &mut MMAPPackedGrid = transmute(maze);
+fn maze_generate_seeded(maze: *mut c_void, generator_type: u32, seed: u64) -> c_int {
+	status(guard(|| {
+		let maze = maze_from_ptr(maze)?;
+		let generator_type = generator_type_from_id(generator_type)?;
+		let seed = Seed::from(seed);
+
+		generate(maze, generator_type, &[GeneratorOption::Seed(seed)])?;
+		maze.set_generator_info(generator_type, Some(seed));
 
-	generate(maze, generator_type, &[]);
+		Ok(())
+	}))
 }
 
+// called with the processed and the total number of cells and the user data given to
+// maze_generate_with_progress, returns 0 to cancel the generation
+pub type MazeProgressCallback = extern "C" fn(u64, u64, *mut c_void) -> c_int;
+
+// the user data is only ever handed back to the callback, which may be called from the
+// threads of the parallel generators
+struct UserData(*mut c_void);
+
+unsafe impl Send for UserData {}
+unsafe impl Sync for UserData {}
+
+// like maze_generate but calls the callback every `interval` cells, returns MAZE_CANCELLED
+// and leaves every wall in place if the callback cancelled the generation
 #[no_mangle]
+/// # Safety
+///
+/// `maze` must be null or a maze returned by `maze_create` or `maze_open` that wasn't freed
+/// yet and isn't used by another thread at the same time.
+///
+/// The callback may be called from other threads than the calling one, with `user_data`
+/// shared between them.
 pub unsafe extern "C"
-fn maze_width(maze: *mut c_void) -> uint64_t {
-	let maze: &mut MMAPPackedGrid = transmute(maze);
+fn maze_generate_with_progress(maze: *mut c_void, generator_type: u32, interval: u64,
+	callback: Option<MazeProgressCallback>, user_data: *mut c_void) -> c_int {
+	status(guard(|| {
+		let maze = maze_from_ptr(maze)?;
+		let generator_type = generator_type_from_id(generator_type)?;
+		let callback = callback.ok_or_else(||
+			Error::InvalidArgument(String::from("progress callback is null")))?;
+		let user_data = UserData(user_data);
 
-	maze.width()
+		let progress = Progress::new(interval, move |processed, total| {
+			callback(processed, total, user_data.0) != 0
+		});
+
+		generate(maze, generator_type, &[GeneratorOption::Progress(progress)])?;
+		maze.set_generator_info(generator_type, None);
+
+		Ok(())
+	}))
 }
 
 #[no_mangle]
+/// # Safety
+///
+/// `maze` must be null or a maze returned by `maze_create` or `maze_open` that wasn't freed
+/// yet and isn't used by another thread at the same time.
 pub unsafe extern "C"
-fn maze_height(maze: *mut c_void) -> uint64_t {
-	let maze: &mut MMAPPackedGrid = transmute(maze);
+fn maze_width(maze: *mut c_void) -> u64 {
+	guard(|| Ok(maze_from_ptr(maze)?.width())).unwrap_or(0)
+}
 
-	maze.height()
+#[no_mangle]
+/// # Safety
+///
+/// `maze` must be null or a maze returned by `maze_create` or `maze_open` that wasn't freed
+/// yet and isn't used by another thread at the same time.
+pub unsafe extern "C"
+fn maze_height(maze: *mut c_void) -> u64 {
+	guard(|| Ok(maze_from_ptr(maze)?.height())).unwrap_or(0)
 }
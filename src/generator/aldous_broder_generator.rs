@@ -0,0 +1,86 @@
+use ::{Grid, Generator, GeneratorOption};
+use ::bitset::BitSet;
+use ::lcg_rng::LCGRng;
+use super::utils::{init_rng, random_neighbour, carve};
+use super::progress::Progress;
+use super::utils::rand::Rng;
+
+// the walks poll the progress every this many steps, they can go on for long without
+// entering a new cell
+pub const WALK_STEPS_PER_CHECK: u64 = 1 << 16;
+
+pub struct AldousBroderGenerator<'a, G: 'a + Grid> {
+	grid: &'a mut G,
+	rng: LCGRng,
+	progress: Progress
+}
+
+impl<'a, G: Grid> AldousBroderGenerator<'a, G> {
+	pub fn new(grid: &'a mut G, options: &[GeneratorOption]) -> Self {
+		let mut seed = None;
+
+		for o in options {
+			if let GeneratorOption::Seed(in_seed) = *o {
+				seed = Some(in_seed);
+			}
+		}
+
+		AldousBroderGenerator {
+			grid,
+			rng: init_rng(seed),
+			progress: Progress::from_options(options)
+		}
+	}
+}
+
+impl<'a, G: Grid> Generator for AldousBroderGenerator<'a, G> {
+	fn generate(&mut self) {
+		let cells = self.grid.width() * self.grid.height();
+		let mut visited = BitSet::new(cells);
+
+		random_walk(self.grid, &mut self.rng, &self.progress, &mut visited, cells);
+	}
+}
+
+// walks the grid from a random cell, carving into every cell the first time it's entered,
+// until `target` cells are visited or it's cancelled, returns the number of visited cells
+pub fn random_walk<G: Grid>(grid: &mut G, rng: &mut LCGRng, progress: &Progress,
+	visited: &mut BitSet, target: u64) -> u64 {
+	let width = grid.width();
+
+	let mut x = (rng.next_f64() * width as f64) as u64;
+	let mut y = (rng.next_f64() * grid.height() as f64) as u64;
+
+	visited.insert(y * width + x);
+	let mut count = 1;
+	let mut steps = 0u64;
+
+	if !progress.advance(1) {
+		return count;
+	}
+
+	while count < target {
+		let (dir, (nx, ny)) = random_neighbour(grid, x, y, rng);
+
+		steps += 1;
+
+		if steps.is_multiple_of(WALK_STEPS_PER_CHECK) && !progress.poll() {
+			break;
+		}
+
+		if !visited.contains(ny * width + nx) {
+			carve(grid, x, y, dir);
+			visited.insert(ny * width + nx);
+			count += 1;
+
+			if !progress.advance(1) {
+				break;
+			}
+		}
+
+		x = nx;
+		y = ny;
+	}
+
+	count
+}
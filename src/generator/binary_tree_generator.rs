@@ -0,0 +1,88 @@
+extern crate num_cpus;
+
+use ::{Grid, Generator, GeneratorOption};
+use ::utils::Direction::{S, E};
+use ::lcg_rng::LCGRng;
+use super::utils::{init_rng, row_rng, row_chunks, SharedGrid};
+use super::progress::Progress;
+use super::utils::rand::Rng;
+
+pub struct BinaryTreeGenerator<'a, G: 'a + Grid> {
+	grid: &'a mut G,
+	rng: LCGRng,
+	thread_count: u64,
+	progress: Progress
+}
+
+impl<'a, G: 'a + Grid> BinaryTreeGenerator<'a, G> {
+	pub fn new(grid: &'a mut G, options: &[GeneratorOption]) -> Self {
+		let mut seed = None;
+		let mut thread_count = num_cpus::get() as u64;
+
+		for o in options {
+			match *o {
+				GeneratorOption::Seed(in_seed) => seed = Some(in_seed),
+				GeneratorOption::ThreadCount(in_thread_count) => thread_count = in_thread_count,
+
+				_ => {}
+			}
+		}
+
+		BinaryTreeGenerator {
+			grid,
+			rng: init_rng(seed),
+			thread_count,
+			progress: Progress::from_options(options)
+		}
+	}
+}
+
+impl<'a, G: 'a + Grid> Generator for BinaryTreeGenerator<'a, G> {
+	fn generate(&mut self) {
+		use std::thread;
+
+		// like the parallel sidewinder every row gets its own generator
+		let base = self.rng.next_u64();
+		let chunks = row_chunks(self.grid.height(), self.thread_count);
+
+		let width = self.grid.width();
+		let progress = &self.progress;
+		let grid = SharedGrid(&mut *self.grid as *mut G);
+		let grid = &grid;
+
+		thread::scope(|scope| {
+			for (start, end) in chunks {
+				scope.spawn(move || {
+					let grid: &mut G = unsafe { &mut *grid.0 };
+
+					for y in start..end {
+						carve_row(grid, y, &mut row_rng(base, y));
+
+						if !progress.advance(width) {
+							return;
+						}
+					}
+				});
+			}
+		});
+	}
+}
+
+// every cell opens either south or east, the last row and column can only go one way
+fn carve_row<G: Grid>(grid: &mut G, y: u64, rng: &mut LCGRng) {
+	let (width, height) = (grid.width(), grid.height());
+
+	for x in 0..width {
+		let dir = if y + 1 == height {
+			E
+		} else if x + 1 == width || rng.next_f64() > 0.50 {
+			S
+		} else {
+			E
+		};
+
+		if x + 1 < width || dir == S {
+			unsafe { grid.or_set_unchecked(x, y, dir as u8); }
+		}
+	}
+}
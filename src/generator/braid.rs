@@ -0,0 +1,100 @@
+use ::{Grid, GeneratorOption};
+use ::utils::Direction;
+use ::utils::Direction::S;
+use super::utils::{row_rng, neighbour, carve};
+use super::utils::rand::Rng;
+
+// the braid gets its own stream of random numbers, derived from the seed like the rows of the
+// parallel generators are, the generator's stream starts with the same numbers otherwise
+const BRAID_STREAM: u64 = 0x6272616964;
+
+// removes `fraction` of the dead ends, rounded, by opening one of their walls. The dead ends
+// are visited in a random order, a wall towards another dead end is preferred as that removes
+// two of them at once, unless only one more is to be removed.
+pub fn braid<G: Grid>(grid: &mut G, fraction: f64, options: &[GeneratorOption]) {
+	let mut seed = None;
+
+	for o in options {
+		if let GeneratorOption::Seed(in_seed) = *o {
+			seed = Some(in_seed);
+		}
+	}
+
+	let mut rng = row_rng(seed.unwrap_or_default().rng_state(), BRAID_STREAM);
+
+	let width = grid.width();
+	let mut dead_ends = Vec::new();
+
+	for y in 0..grid.height() {
+		for x in 0..width {
+			if passages(grid, x, y) == 1 {
+				dead_ends.push(y * width + x);
+			}
+		}
+	}
+
+	let target = (fraction * dead_ends.len() as f64).round() as u64;
+	let mut removed = 0;
+
+	// shuffled as they're visited
+	for i in 0..dead_ends.len() {
+		if removed >= target {
+			break;
+		}
+
+		let j = i + (rng.next_f64() * (dead_ends.len() - i) as f64) as usize;
+		dead_ends.swap(i, j);
+
+		let (x, y) = (dead_ends[i] % width, dead_ends[i] / width);
+
+		// dead ends may already be gone because of a neighbour
+		if passages(grid, x, y) != 1 {
+			continue;
+		}
+
+		let mut walls = [S; 4];
+		let mut wall_count = 0;
+		let mut dead_end_walls = [S; 4];
+		let mut dead_end_count = 0;
+
+		for &dir in Direction::enumerate() {
+			if let Some((nx, ny)) = neighbour(grid, x, y, dir) {
+				if !grid.test(x, y, dir as u8) {
+					// a cell without passages would just become the next dead end
+					match passages(grid, nx, ny) {
+						0 => {},
+						1 => {
+							dead_end_walls[dead_end_count] = dir;
+							dead_end_count += 1;
+						},
+						_ => {
+							walls[wall_count] = dir;
+							wall_count += 1;
+						}
+					}
+				}
+			}
+		}
+
+		let dir = if dead_end_count > 0 && target - removed >= 2 {
+			removed += 2;
+			dead_end_walls[(rng.next_f64() * dead_end_count as f64) as usize]
+		} else if wall_count > 0 {
+			removed += 1;
+			walls[(rng.next_f64() * wall_count as f64) as usize]
+		} else {
+			// the ends of a corridor that is one cell wide have nothing to open, and opening
+			// towards another dead end would remove one too many
+			continue;
+		};
+
+		carve(grid, x, y, dir);
+	}
+}
+
+// the number of passages leading to cells inside of the grid
+fn passages<G: Grid>(grid: &G, x: u64, y: u64) -> usize {
+	Direction::enumerate().iter()
+		.filter(|&&dir| grid.test(x, y, dir as u8) && neighbour(grid, x, y, dir).is_some())
+		.count()
+}
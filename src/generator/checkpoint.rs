@@ -0,0 +1,261 @@
+// Generators that support checkpoints periodically save their state to a file, a later
+// generation with the same checkpoint file picks up from there and the finished maze is
+// the same as that of an uninterrupted generation.
+//
+// The grid must never get ahead of the last checkpoint, so the order of every checkpoint is:
+// flush the grid, write the state to a temporary file and rename it over the checkpoint,
+// and only then apply the changes that were buffered since the previous checkpoint. Those
+// changes are part of the checkpoint, so a crash while applying them is repaired on resume.
+//
+// Layout, all integers are little endian:
+//
+//   offset  size  field
+//        0     8  magic, the bytes "MAZECKPT"
+//        8     4  format version, 2
+//       12     4  generator, the GeneratorType that wrote the checkpoint
+//       16     8  width of the grid
+//       24     8  height of the grid
+//       32     8  state of the random number generator
+//       40        state of the generator, up to the generator
+//      end-4     4  CRC-32 of everything before it
+
+extern crate byteorder;
+
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write, Cursor};
+use ::{Grid, GeneratorType, GeneratorOption};
+use ::crc32::Crc32;
+use ::error::{Error, Result};
+use ::lcg_rng::LCGRng;
+use self::byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use super::utils::rand::SeedableRng;
+
+const MAGIC: &[u8; 8] = b"MAZECKPT";
+const FORMAT_VERSION: u32 = 2;
+
+pub struct Checkpoint {
+	path: String,
+	// the number of cells between two checkpoints
+	interval: u64,
+	since_last: u64
+}
+
+// the state read back from a checkpoint, `state` is what the generator wrote after the header
+pub struct Resumed {
+	pub rng: LCGRng,
+	pub state: Cursor<Vec<u8>>
+}
+
+impl Checkpoint {
+	pub fn from_options(options: &[GeneratorOption]) -> Option<Self> {
+		for o in options {
+			if let GeneratorOption::Checkpoint(ref path, interval) = *o {
+				return Some(Checkpoint {
+					path: path.clone(),
+					interval: interval.max(1),
+					since_last: 0
+				});
+			}
+		}
+
+		None
+	}
+
+	// counts processed cells, returns true once a checkpoint is due
+	pub fn advance(&mut self, cells: u64) -> bool {
+		self.since_last += cells;
+
+		if self.since_last >= self.interval {
+			self.since_last = 0;
+			true
+		} else {
+			false
+		}
+	}
+
+	// the saved state if there is a checkpoint to resume from
+	pub fn load<G: Grid>(&self, grid: &G, generator_type: GeneratorType) -> Result<Option<Resumed>> {
+		let mut bytes = Vec::new();
+
+		match File::open(&self.path) {
+			Ok(mut file) => { file.read_to_end(&mut bytes)?; },
+			Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+			Err(e) => return Err(Error::from(e))
+		}
+
+		if bytes.len() < 44 || &bytes[..8] != MAGIC {
+			return Err(invalid("not a checkpoint file"));
+		}
+
+		let (body, crc) = bytes.split_at(bytes.len() - 4);
+		let mut checksum = Crc32::new();
+		checksum.update(body);
+
+		if checksum.finish() != (&crc[..]).read_u32::<LittleEndian>()? {
+			return Err(invalid("the checkpoint is corrupted"));
+		}
+
+		let mut header = &body[8..40];
+
+		if header.read_u32::<LittleEndian>()? != FORMAT_VERSION {
+			return Err(invalid("unsupported checkpoint version"));
+		}
+
+		if header.read_u32::<LittleEndian>()? != generator_type as u32 {
+			return Err(invalid("the checkpoint is of a different generator"));
+		}
+
+		if header.read_u64::<LittleEndian>()? != grid.width()
+			|| header.read_u64::<LittleEndian>()? != grid.height() {
+			return Err(invalid("the checkpoint is of a grid with different dimensions"));
+		}
+
+		let rng = LCGRng::from_seed(header.read_u64::<LittleEndian>()?);
+		let state = body[40..].to_vec();
+
+		Ok(Some(Resumed {
+			rng,
+			state: Cursor::new(state)
+		}))
+	}
+
+	// makes the grid durable and atomically replaces the checkpoint with the given state
+	pub fn save<G: Grid>(&self, grid: &G, generator_type: GeneratorType, rng: &LCGRng, state: &[u8])
+		-> Result<()> {
+		grid.flush()?;
+
+		let mut bytes = Vec::with_capacity(44 + state.len());
+
+		bytes.extend_from_slice(MAGIC);
+		bytes.write_u32::<LittleEndian>(FORMAT_VERSION)?;
+		bytes.write_u32::<LittleEndian>(generator_type as u32)?;
+		bytes.write_u64::<LittleEndian>(grid.width())?;
+		bytes.write_u64::<LittleEndian>(grid.height())?;
+		bytes.write_u64::<LittleEndian>(rng.state())?;
+		bytes.extend_from_slice(state);
+
+		let mut checksum = Crc32::new();
+		checksum.update(&bytes);
+		let crc = checksum.finish();
+		bytes.write_u32::<LittleEndian>(crc)?;
+
+		let temporary = format!("{}.tmp", self.path);
+		let mut file = OpenOptions::new().create(true).truncate(true).write(true).open(&temporary)?;
+
+		file.write_all(&bytes)?;
+		file.sync_all()?;
+		fs::rename(&temporary, &self.path)?;
+
+		Ok(())
+	}
+
+	// the generation finished, there is nothing left to resume
+	pub fn remove(&self) -> Result<()> {
+		match fs::remove_file(&self.path) {
+			Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+			result => result.map_err(Error::from)
+		}
+	}
+}
+
+fn invalid(message: &str) -> Error {
+	Error::InvalidFormat(String::from(message))
+}
+
+// buffers the changes to a grid between two checkpoints, reads see the buffered changes
+pub struct JournaledGrid<'a, G: 'a + Grid> {
+	grid: &'a mut G,
+	changes: HashMap<(u64, u64), u8>
+}
+
+impl<'a, G: Grid> JournaledGrid<'a, G> {
+	pub fn new(grid: &'a mut G) -> Self {
+		JournaledGrid {
+			grid,
+			changes: HashMap::new()
+		}
+	}
+
+	pub fn grid(&self) -> &G {
+		self.grid
+	}
+
+	pub fn write_changes<W: Write>(&self, out: &mut W) -> Result<()> {
+		out.write_u64::<LittleEndian>(self.changes.len() as u64)?;
+
+		for (&(x, y), &value) in self.changes.iter() {
+			out.write_u64::<LittleEndian>(x)?;
+			out.write_u64::<LittleEndian>(y)?;
+			out.write_u8(value)?;
+		}
+
+		Ok(())
+	}
+
+	pub fn read_changes<R: Read>(&mut self, input: &mut R) -> Result<()> {
+		for _ in 0..input.read_u64::<LittleEndian>()? {
+			let x = input.read_u64::<LittleEndian>()?;
+			let y = input.read_u64::<LittleEndian>()?;
+			let value = input.read_u8()?;
+
+			if x >= self.grid.width() || y >= self.grid.height() {
+				return Err(invalid("the checkpoint has changes outside of the grid"));
+			}
+
+			self.changes.insert((x, y), value);
+		}
+
+		Ok(())
+	}
+
+	// writes the buffered changes through to the grid
+	pub fn apply(&mut self) {
+		for (&(x, y), &value) in self.changes.iter() {
+			unsafe { self.grid.set_unchecked(x, y, value); }
+		}
+
+		self.changes.clear();
+	}
+}
+
+impl<'a, G: Grid> Grid for JournaledGrid<'a, G> {
+	fn width(&self) -> u64 {
+		self.grid.width()
+	}
+
+	fn height(&self) -> u64 {
+		self.grid.height()
+	}
+
+	// not buffered, the grid is filled right away
+	fn fill(&mut self, fill: u8) {
+		self.changes.clear();
+		self.grid.fill(fill);
+	}
+
+	unsafe fn get_unchecked(&self, x: u64, y: u64) -> u8 {
+		match self.changes.get(&(x, y)) {
+			Some(&value) => value,
+			None => self.grid.get_unchecked(x, y)
+		}
+	}
+
+	unsafe fn set_unchecked(&mut self, x: u64, y: u64, value: u8) {
+		self.changes.insert((x, y), value & 0b11);
+	}
+
+	unsafe fn or_set_unchecked(&mut self, x: u64, y: u64, value: u8) {
+		let old = self.get_unchecked(x, y);
+		self.changes.insert((x, y), (old | value) & 0b11);
+	}
+
+	unsafe fn unset_provided_unchecked(&mut self, x: u64, y: u64, value: u8) {
+		let old = self.get_unchecked(x, y);
+		self.changes.insert((x, y), old & !value & 0b11);
+	}
+
+	fn flush(&self) -> Result<()> {
+		self.grid.flush()
+	}
+}
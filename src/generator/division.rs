@@ -0,0 +1,121 @@
+use std::collections::BTreeSet;
+use ::{Grid, GeneratorOption};
+use ::utils::Direction::*;
+use ::lcg_rng::LCGRng;
+use super::utils::rand::Rng;
+
+// the options shared by the division generators, the defaults divide down to corridors
+// of a single cell with one opening in every wall
+pub struct DivisionOptions {
+	room_size: u64,
+	orientation_bias: f64,
+	wall_openings: u64,
+	undivided_chance: f64
+}
+
+impl DivisionOptions {
+	pub fn new(options: &[GeneratorOption]) -> Self {
+		let mut division_options = DivisionOptions {
+			room_size: 2,
+			orientation_bias: 0.35,
+			wall_openings: 1,
+			undivided_chance: 0.0
+		};
+
+		for o in options {
+			match *o {
+				GeneratorOption::RoomSize(size) => division_options.room_size = size,
+				GeneratorOption::OrientationBias(bias) => division_options.orientation_bias = bias,
+				GeneratorOption::WallOpenings(openings) => division_options.wall_openings = openings,
+				GeneratorOption::UndividedChance(chance) => division_options.undivided_chance = chance,
+
+				_ => {}
+			}
+		}
+
+		division_options
+	}
+}
+
+// draws a wall through the chamber and returns the two chambers on either side of it, or
+// nothing if the chamber is left as it is
+pub fn divide<G: Grid>(grid: &mut G, rng: &mut LCGRng, options: &DivisionOptions,
+	x: u64, y: u64, width: u64, height: u64) -> Option<[(u64, u64, u64, u64); 2]> {
+	use ::utils::Orientation::*;
+
+	let bias = options.orientation_bias;
+
+	// randomly choose an orientation biasing shorter corridors
+	let orientation =
+		if rng.next_f64() + if height > width { bias } else { -bias } > 0.5 {
+			Horizontal
+		} else {
+			Vertical
+		};
+
+	// we can't divide something that is too narrow, chambers smaller than a room stay open
+	if width < options.room_size.max(2) || height < options.room_size.max(2) {
+		return None;
+	}
+
+	if options.undivided_chance > 0.0 && rng.next_f64() < options.undivided_chance {
+		return None;
+	}
+
+	// where the line will start
+	let (mx, my) = match orientation {
+		Horizontal => (x, y + rng.gen_range(0, height - 1)),
+		Vertical => (x + rng.gen_range(0, width - 1), y)
+	};
+	// in which direction we should offset each iteration
+	let (dx, dy) = match orientation {
+		Horizontal => (1, 0),
+		Vertical => (0, 1)
+	};
+	// how long the bisector line will be
+	let length = match orientation {
+		Horizontal => width,
+		Vertical => height
+	};
+	// where the line will have its openings, all of them distinct, drawn with Floyd's
+	// algorithm so every draw gives a new one, in order to be walked along with the line
+	let mut openings = BTreeSet::new();
+
+	for j in length - options.wall_openings.min(length)..length {
+		let opening = rng.gen_range(0, j + 1);
+
+		if !openings.insert(opening) {
+			openings.insert(j);
+		}
+	}
+
+	let mut openings = openings.into_iter().peekable();
+	// in which direction we should set the bisector line
+	let direction = match orientation {
+		Horizontal => S,
+		Vertical => E
+	};
+
+	// draw the dividing line
+	for i in 0..length {
+		// except for where we've chosen the holes to be
+		if openings.peek() == Some(&i) {
+			openings.next();
+		} else {
+			unsafe {
+				grid.unset_provided_unchecked(mx + dx * i, my + dy * i, direction as u8);
+			}
+		}
+	}
+
+	Some(match orientation {
+		Horizontal => [
+			(x, y, width, my - y + 1),
+			(x, my + 1, width, y + height - my - 1)
+		],
+		Vertical => [
+			(x, y, mx - x + 1, height),
+			(mx + 1, y, x + width - mx - 1, height)
+		]
+	})
+}
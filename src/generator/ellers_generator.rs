@@ -0,0 +1,195 @@
+extern crate byteorder;
+
+use ::{Grid, Generator, GeneratorOption, GeneratorType};
+use ::error::{Error, Result};
+use ::utils::Direction::{S, E};
+use ::lcg_rng::LCGRng;
+use super::utils::init_rng;
+use super::progress::Progress;
+use super::checkpoint::Checkpoint;
+use super::utils::rand::Rng;
+use self::byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
+pub struct EllersGenerator<'a, G: 'a + Grid> {
+	grid: &'a mut G,
+	rng: LCGRng,
+	progress: Progress,
+	checkpoint: Option<Checkpoint>,
+	error: Option<Error>
+}
+
+impl<'a, G: Grid> EllersGenerator<'a, G> {
+	pub fn new(grid: &'a mut G, options: &[GeneratorOption]) -> Self {
+		let mut seed = None;
+
+		for o in options {
+			if let GeneratorOption::Seed(in_seed) = *o {
+				seed = Some(in_seed);
+			}
+		}
+
+		EllersGenerator {
+			grid,
+			rng: init_rng(seed),
+			progress: Progress::from_options(options),
+			checkpoint: Checkpoint::from_options(options),
+			error: None
+		}
+	}
+
+	// rows are carved without looking at the grid, so the rows carved after the checkpoint
+	// carve the same passages again when resumed
+	fn save(&self, checkpoint: &Checkpoint, y: u64, sets: &[usize], rng: &LCGRng) -> Result<()> {
+		let mut state = Vec::with_capacity(8 + sets.len() * 8);
+
+		state.write_u64::<LittleEndian>(y)?;
+
+		for &set in sets {
+			state.write_u64::<LittleEndian>(set as u64)?;
+		}
+
+		checkpoint.save(self.grid, GeneratorType::EllersAlgorithm, rng, &state)
+	}
+
+	fn carve_rows(&mut self) -> Result<()> {
+		let (width, height) = (self.grid.width(), self.grid.height());
+		let w = width as usize;
+
+		// the set every cell of the current row belongs to, set ids are always < width
+		let mut sets: Vec<usize> = (0..w).collect();
+		// union-find over the set ids, used while merging sets within a row
+		let mut parent: Vec<usize> = (0..w).collect();
+		// how many cells of a set are still to be visited in the current row
+		let mut remaining = vec![0usize; w];
+		// whether a set already has a passage leading down from the current row
+		let mut carried = vec![false; w];
+		// whether a cell of the current row has a passage leading down
+		let mut down = vec![false; w];
+		// set ids that are not used by the next row
+		let mut free = Vec::with_capacity(w);
+
+		let mut checkpoint = self.checkpoint.take();
+		let mut start = 0;
+
+		if let Some(ref checkpoint) = checkpoint {
+			if let Some(mut resumed) = checkpoint.load(self.grid, GeneratorType::EllersAlgorithm)? {
+				self.rng = resumed.rng;
+				start = resumed.state.read_u64::<LittleEndian>()?;
+
+				for set in sets.iter_mut() {
+					*set = resumed.state.read_u64::<LittleEndian>()? as usize;
+
+					if *set >= w || start >= height {
+						return Err(Error::InvalidFormat(String::from("the checkpoint has an invalid row")));
+					}
+				}
+
+				self.progress.advance(start * width);
+			}
+		}
+
+		// the generator as it was at the start of the row, in case the row is interrupted
+		let mut row_rng = self.rng.clone();
+		let mut row = start;
+
+		for y in start..height {
+			let last_row = y + 1 == height;
+
+			if let Some(ref mut checkpoint) = checkpoint {
+				if y > start && checkpoint.advance(width) {
+					self.save(checkpoint, y, &sets, &self.rng)?;
+				}
+
+				row_rng = self.rng.clone();
+				row = y;
+			}
+
+			for (id, parent) in parent.iter_mut().enumerate() {
+				*parent = id;
+			}
+
+			// randomly join adjacent cells of different sets, the last row joins all of them
+			for x in 0..(w - 1) {
+				let a = find(&mut parent, sets[x]);
+				let b = find(&mut parent, sets[x + 1]);
+
+				if a != b && (last_row || self.rng.next_f64() > 0.50) {
+					unsafe { self.grid.or_set_unchecked(x as u64, y, E as u8); }
+					parent[b] = a;
+				}
+			}
+
+			if last_row || !self.progress.advance(width) {
+				break;
+			}
+
+			for id in 0..w {
+				remaining[id] = 0;
+				carried[id] = false;
+			}
+
+			for x in 0..w {
+				sets[x] = find(&mut parent, sets[x]);
+				remaining[sets[x]] += 1;
+			}
+
+			// randomly carve down, making sure every set continues on the next row
+			for x in 0..w {
+				let set = sets[x];
+				remaining[set] -= 1;
+
+				let forced = !carried[set] && remaining[set] == 0;
+
+				if forced || self.rng.next_f64() > 0.50 {
+					unsafe { self.grid.or_set_unchecked(x as u64, y, S as u8); }
+					carried[set] = true;
+					down[x] = true;
+				} else {
+					down[x] = false;
+				}
+			}
+
+			// cells not reached from above start out in sets of their own
+			free.clear();
+			free.extend((0..w).filter(|&id| !carried[id]));
+
+			for x in 0..w {
+				if !down[x] {
+					sets[x] = free.pop().unwrap();
+				}
+			}
+		}
+
+		if let Some(ref checkpoint) = checkpoint {
+			if self.progress.is_cancelled() {
+				self.save(checkpoint, row, &sets, &row_rng)?;
+			} else {
+				self.grid.flush()?;
+				checkpoint.remove()?;
+			}
+		}
+
+		Ok(())
+	}
+}
+
+impl<'a, G: Grid> Generator for EllersGenerator<'a, G> {
+	fn generate(&mut self) {
+		if let Err(e) = self.carve_rows() {
+			self.error = Some(e);
+		}
+	}
+
+	fn take_error(&mut self) -> Option<Error> {
+		self.error.take()
+	}
+}
+
+fn find(parent: &mut [usize], mut id: usize) -> usize {
+	while parent[id] != id {
+		parent[id] = parent[parent[id]];
+		id = parent[id];
+	}
+
+	id
+}
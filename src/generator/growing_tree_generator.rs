@@ -0,0 +1,139 @@
+use std::collections::VecDeque;
+use ::{Grid, Generator, GeneratorOption};
+use ::utils::Direction;
+use ::utils::Direction::{S, E};
+use ::lcg_rng::LCGRng;
+use super::utils::{init_rng, neighbour, carve};
+use super::progress::Progress;
+use super::utils::rand::Rng;
+
+// how the next cell to carve from is picked out of the cells that may still have unvisited
+// neighbours
+#[derive(Clone, Copy, Debug)]
+pub enum CellSelection {
+	// the last cell added, carves like the backtracking generators
+	Newest,
+	// any cell, gives a texture close to Prim's
+	Random,
+	// the first cell added, gives long straight corridors
+	Oldest,
+	// weights of newest, random and oldest, e.g. Mix(3.0, 1.0, 0.0) picks the newest cell 75%
+	// of the time and a random one otherwise
+	Mix(f64, f64, f64)
+}
+
+pub struct GrowingTreeGenerator<'a, G: 'a + Grid> {
+	grid: &'a mut G,
+	rng: LCGRng,
+	selection: CellSelection,
+	progress: Progress
+}
+
+impl<'a, G: Grid> GrowingTreeGenerator<'a, G> {
+	pub fn new(grid: &'a mut G, options: &[GeneratorOption]) -> Self {
+		let mut seed = None;
+		let mut selection = CellSelection::Newest;
+
+		for o in options {
+			match *o {
+				GeneratorOption::Seed(in_seed) => seed = Some(in_seed),
+				GeneratorOption::CellSelection(in_selection) => selection = in_selection,
+
+				_ => {}
+			}
+		}
+
+		GrowingTreeGenerator {
+			grid,
+			rng: init_rng(seed),
+			selection,
+			progress: Progress::from_options(options)
+		}
+	}
+
+	// the index of the next cell to carve from and the selection that picked it
+	fn select(&mut self, len: usize) -> (usize, CellSelection) {
+		use self::CellSelection::*;
+
+		let selection = match self.selection {
+			Mix(newest, random, oldest) => {
+				let pick = self.rng.next_f64() * (newest + random + oldest);
+
+				if pick < newest {
+					Newest
+				} else if pick < newest + random {
+					Random
+				} else {
+					Oldest
+				}
+			},
+			selection => selection
+		};
+
+		let index = match selection {
+			Newest => len - 1,
+			Oldest => 0,
+			_ => (self.rng.next_f64() * len as f64) as usize
+		};
+
+		(index, selection)
+	}
+
+	// the same walk as stack_backtrack_generator::stack_carve, every active cell draws the order
+	// of its directions once when it's added and remembers how many of them were tried already,
+	// a direction that was skipped leads to a visited cell so it never has to be tried again
+	fn grow(&mut self, sx: u64, sy: u64) {
+		let directions = Direction::enumerate();
+
+		let mut active = VecDeque::new();
+		active.push_back((sx, sy, self.direction_offset(), 0));
+
+		while !active.is_empty() {
+			let (index, selection) = self.select(active.len());
+			let (x, y, direction_offset, first) = active[index];
+			let mut carved = false;
+
+			for i in first..4 {
+				let dir = directions[(direction_offset + i * 3) % directions.len()];
+
+				if let Some((nx, ny)) = neighbour(self.grid, x, y, dir) {
+					let mut val = self.grid.get(nx, ny);
+					if nx > 0 { val |= self.grid.get(nx - 1, ny) & E as u8 };
+					if ny > 0 { val |= self.grid.get(nx, ny - 1) & S as u8 };
+
+					if val == 0 {
+						carve(self.grid, x, y, dir);
+
+						active[index].3 = i + 1;
+						active.push_back((nx, ny, self.direction_offset(), 0));
+						carved = true;
+
+						break;
+					}
+				}
+			}
+
+			if !carved {
+				// random picks swap the newest cell into their place, in a mix that cell then
+				// loses its place as the newest one
+				match selection {
+					CellSelection::Newest => { active.pop_back(); },
+					CellSelection::Oldest => { active.pop_front(); },
+					_ => { active.swap_remove_back(index); }
+				}
+			} else if !self.progress.advance(1) {
+				return;
+			}
+		}
+	}
+
+	fn direction_offset(&mut self) -> usize {
+		(self.rng.next_f64() * Direction::enumerate().len() as f64) as usize
+	}
+}
+
+impl<'a, G: Grid> Generator for GrowingTreeGenerator<'a, G> {
+	fn generate(&mut self) {
+		self.grow(0, 0);
+	}
+}
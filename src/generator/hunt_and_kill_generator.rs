@@ -0,0 +1,127 @@
+use ::{Grid, Generator, GeneratorOption};
+use ::utils::Direction;
+use ::utils::Direction::{S, E};
+use ::lcg_rng::LCGRng;
+use super::utils::{init_rng, neighbour, carve};
+use super::progress::Progress;
+use super::utils::rand::Rng;
+
+pub struct HuntAndKillGenerator<'a, G: 'a + Grid> {
+	grid: &'a mut G,
+	rng: LCGRng,
+	progress: Progress
+}
+
+impl<'a, G: Grid> HuntAndKillGenerator<'a, G> {
+	pub fn new(grid: &'a mut G, options: &[GeneratorOption]) -> Self {
+		let mut seed = None;
+
+		for o in options {
+			if let GeneratorOption::Seed(in_seed) = *o {
+				seed = Some(in_seed);
+			}
+		}
+
+		HuntAndKillGenerator {
+			grid,
+			rng: init_rng(seed),
+			progress: Progress::from_options(options)
+		}
+	}
+
+	// a cell is part of the maze as soon as it has a passage, so no state is kept besides
+	// the grid itself
+	fn visited(&self, x: u64, y: u64) -> bool {
+		let mut val = self.grid.get(x, y);
+		if x > 0 { val |= self.grid.get(x - 1, y) & E as u8 };
+		if y > 0 { val |= self.grid.get(x, y - 1) & S as u8 };
+
+		val != 0
+	}
+
+	// picks a random neighbour that is (or isn't) part of the maze yet
+	fn random_neighbour(&mut self, x: u64, y: u64, visited: bool) -> Option<Direction> {
+		let mut candidates = [S; 4];
+		let mut count = 0;
+
+		for &dir in Direction::enumerate() {
+			if let Some((nx, ny)) = neighbour(self.grid, x, y, dir) {
+				if self.visited(nx, ny) == visited {
+					candidates[count] = dir;
+					count += 1;
+				}
+			}
+		}
+
+		if count == 0 {
+			None
+		} else {
+			Some(candidates[(self.rng.next_f64() * count as f64) as usize])
+		}
+	}
+
+	// carves into unvisited cells until the walk runs into a dead end
+	fn kill(&mut self, mut x: u64, mut y: u64) {
+		while let Some(dir) = self.random_neighbour(x, y, false) {
+			carve(self.grid, x, y, dir);
+
+			if !self.progress.advance(1) {
+				return;
+			}
+
+			let (nx, ny) = neighbour(self.grid, x, y, dir).unwrap();
+			x = nx;
+			y = ny;
+		}
+	}
+
+	// looks for an unvisited cell next to the maze and connects it, rows above `row` are
+	// known to be fully visited
+	fn hunt(&mut self, row: &mut u64) -> Option<(u64, u64)> {
+		for y in *row..self.grid.height() {
+			let mut complete = true;
+
+			for x in 0..self.grid.width() {
+				if self.visited(x, y) {
+					continue;
+				}
+
+				complete = false;
+
+				if let Some(dir) = self.random_neighbour(x, y, true) {
+					carve(self.grid, x, y, dir);
+					self.progress.advance(1);
+
+					return Some((x, y));
+				}
+			}
+
+			if complete && y == *row {
+				*row += 1;
+			}
+		}
+
+		None
+	}
+}
+
+impl<'a, G: Grid> Generator for HuntAndKillGenerator<'a, G> {
+	fn generate(&mut self) {
+		let mut x = (self.rng.next_f64() * self.grid.width() as f64) as u64;
+		let mut y = (self.rng.next_f64() * self.grid.height() as f64) as u64;
+		let mut row = 0;
+
+		loop {
+			self.kill(x, y);
+
+			if self.progress.is_cancelled() {
+				return;
+			}
+
+			match self.hunt(&mut row) {
+				Some((nx, ny)) => { x = nx; y = ny; },
+				None => break
+			}
+		}
+	}
+}
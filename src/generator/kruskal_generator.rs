@@ -0,0 +1,194 @@
+// Every interior edge is visited once in a random order and carved when it joins two
+// different trees. The edges are never stored: their order comes from a keyed permutation
+// of the edge indices, so the only state that grows with the grid is the union-find, which
+// can be kept in a memory mapped side file for grids that don't fit in memory.
+
+use ::{Grid, Generator, GeneratorOption};
+use ::error::Result;
+use ::utils::Direction::{S, E};
+use ::lcg_rng::LCGRng;
+use super::utils::init_rng;
+use super::progress::Progress;
+use super::slots::Slots;
+use super::utils::rand::Rng;
+
+const ROUNDS: usize = 4;
+
+pub struct KruskalGenerator<'a, G: 'a + Grid> {
+	grid: &'a mut G,
+	rng: LCGRng,
+	sets: DisjointSets,
+	progress: Progress
+}
+
+impl<'a, G: Grid> KruskalGenerator<'a, G> {
+	pub fn new(grid: &'a mut G, options: &[GeneratorOption]) -> Result<Self> {
+		let mut seed = None;
+		let mut path = None;
+
+		for o in options {
+			match *o {
+				GeneratorOption::Seed(in_seed) => seed = Some(in_seed),
+				GeneratorOption::UnionFindFilePath(ref in_path) => path = Some(in_path.clone()),
+
+				_ => {}
+			}
+		}
+
+		let cells = grid.width() * grid.height();
+
+		Ok(KruskalGenerator {
+			sets: DisjointSets {
+				slots: match path {
+					Some(path) => Slots::mapped(cells, path)?,
+					None => Slots::in_memory(cells)
+				}
+			},
+			grid,
+			rng: init_rng(seed),
+			progress: Progress::from_options(options)
+		})
+	}
+
+	fn join_edges(&mut self) {
+		let (width, height) = (self.grid.width(), self.grid.height());
+
+		// the edges east of every cell but the last column come first, then the ones south
+		// of every cell but the last row
+		let east_edges = (width - 1) * height;
+		let edges = east_edges + width * (height - 1);
+
+		let permutation = Permutation::new(edges, &mut self.rng);
+
+		for i in 0..edges {
+			let edge = permutation.get(i);
+
+			let (x, y, dir) = if edge < east_edges {
+				(edge % (width - 1), edge / (width - 1), E)
+			} else {
+				((edge - east_edges) % width, (edge - east_edges) / width, S)
+			};
+
+			let cell = y * width + x;
+			let neighbour = if dir == E { cell + 1 } else { cell + width };
+
+			if self.sets.union(cell, neighbour, &mut self.rng) {
+				self.grid.or_set(x, y, dir as u8);
+
+				if !self.progress.advance(1) {
+					return;
+				}
+			}
+		}
+	}
+}
+
+impl<'a, G: Grid> Generator for KruskalGenerator<'a, G> {
+	fn generate(&mut self) {
+		self.join_edges();
+		self.sets.slots.remove_file();
+	}
+}
+
+// a union-find over the cell indices, a slot holds the parent index plus one so a zeroed slot
+// is a root, fresh slots then need no initialization
+struct DisjointSets {
+	slots: Slots
+}
+
+impl DisjointSets {
+	fn find(&mut self, mut index: u64) -> u64 {
+		let slots = self.slots.get_mut();
+
+		// path splitting, every visited slot is pointed at its grandparent
+		while slots[index as usize] != 0 {
+			let parent = slots[index as usize] - 1;
+			let grandparent = slots[parent as usize];
+
+			if grandparent != 0 {
+				slots[index as usize] = grandparent;
+			}
+
+			index = parent;
+		}
+
+		index
+	}
+
+	// joins the sets of both cells, returns false if they're in the same set already
+	fn union<R: Rng>(&mut self, a: u64, b: u64, rng: &mut R) -> bool {
+		let (a, b) = (self.find(a), self.find(b));
+
+		if a == b {
+			return false;
+		}
+
+		// randomized linking keeps the trees shallow without storing ranks
+		let (child, root) = if rng.next_u32() & 1 == 0 { (a, b) } else { (b, a) };
+		self.slots.get_mut()[child as usize] = root + 1;
+
+		true
+	}
+
+}
+
+// a keyed Feistel network over the smallest power of 4 covering the domain, indices that
+// fall outside of the domain are encrypted again until they land inside (cycle walking)
+struct Permutation {
+	len: u64,
+	half_bits: u32,
+	keys: [u64; ROUNDS]
+}
+
+impl Permutation {
+	fn new<R: Rng>(len: u64, rng: &mut R) -> Self {
+		let mut half_bits = 1;
+
+		while half_bits < 32 && (1u64 << (2 * half_bits)) < len {
+			half_bits += 1;
+		}
+
+		let mut keys = [0; ROUNDS];
+
+		for key in keys.iter_mut() {
+			*key = rng.next_u64();
+		}
+
+		Permutation {
+			len,
+			half_bits,
+			keys
+		}
+	}
+
+	fn get(&self, index: u64) -> u64 {
+		let mut index = self.encrypt(index);
+
+		while index >= self.len {
+			index = self.encrypt(index);
+		}
+
+		index
+	}
+
+	fn encrypt(&self, index: u64) -> u64 {
+		let mask = (1u64 << self.half_bits) - 1;
+		let (mut left, mut right) = (index >> self.half_bits, index & mask);
+
+		for &key in self.keys.iter() {
+			let next = left ^ (mix(right ^ key) & mask);
+
+			left = right;
+			right = next;
+		}
+
+		(left << self.half_bits) | right
+	}
+}
+
+fn mix(mut z: u64) -> u64 {
+	z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+	z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+
+	z ^ (z >> 31)
+}
@@ -1,28 +1,134 @@
 mod sidewinder_generator;
+mod parallel_sidewinder_generator;
 mod naive_sidewinder_generator;
 mod recursive_backtrack_generator;
 mod stack_backtrack_generator;
 mod recursive_division_generator;
 mod stack_division_generator;
+mod ellers_generator;
+mod prims_generator;
+mod kruskal_generator;
+mod aldous_broder_generator;
+mod wilsons_generator;
+mod growing_tree_generator;
+mod hunt_and_kill_generator;
+mod binary_tree_generator;
+mod braid;
+mod division;
+mod steps;
+mod progress;
+mod checkpoint;
+mod slots;
+mod seed;
 
 pub use self::sidewinder_generator::SidewinderGenerator;
+pub use self::parallel_sidewinder_generator::ParallelSidewinderGenerator;
 pub use self::naive_sidewinder_generator::NaiveSidewinderGenerator;
 pub use self::recursive_backtrack_generator::RecursiveBacktrackGenerator;
 pub use self::stack_backtrack_generator::StackBacktrackGenerator;
 pub use self::recursive_division_generator::RecursiveDivisionGenerator;
 pub use self::stack_division_generator::StackDivisionGenerator;
+pub use self::ellers_generator::EllersGenerator;
+pub use self::prims_generator::PrimsGenerator;
+pub use self::kruskal_generator::KruskalGenerator;
+pub use self::aldous_broder_generator::AldousBroderGenerator;
+pub use self::wilsons_generator::WilsonsGenerator;
+pub use self::growing_tree_generator::{GrowingTreeGenerator, CellSelection};
+pub use self::hunt_and_kill_generator::HuntAndKillGenerator;
+pub use self::binary_tree_generator::BinaryTreeGenerator;
+pub use self::braid::braid;
+pub use self::steps::{Steps, Step};
+pub use self::progress::Progress;
+pub use self::seed::Seed;
 
 mod utils {
-	extern crate rand;
+	pub extern crate rand;
 
+	use ::Grid;
 	use ::lcg_rng::LCGRng;
-	use self::rand::SeedableRng;
+	use ::utils::Direction;
+	use super::Seed;
+	use self::rand::{Rng, SeedableRng};
 
-	pub fn init_rng(seed: Option<&[u32]>) -> LCGRng {
-		if let Some(s) = seed {
-			LCGRng::from_seed((s[0] as u64) + (s[1] as u64) << 32)
-		} else {
-			LCGRng::new_unseeded()
+	// without a seed the generators start from the same state as with a seed of 0
+	pub fn init_rng(seed: Option<Seed>) -> LCGRng {
+		LCGRng::from_seed(seed.unwrap_or_default().rng_state())
+	}
+
+	// derives an independent generator for a single row, so rows can be carved in any order
+	pub fn row_rng(base: u64, row: u64) -> LCGRng {
+		let mut z = base ^ row.wrapping_mul(0x9E3779B97F4A7C15);
+
+		z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+
+		LCGRng::from_seed(z ^ (z >> 31))
+	}
+
+	// splits the rows into at most `threads` ranges, every range starts on a multiple of 4 rows
+	// so no two ranges ever share a packed byte
+	pub fn row_chunks(height: u64, threads: u64) -> Vec<(u64, u64)> {
+		use std::cmp::{min, max};
+
+		let rows = height.div_ceil(max(threads, 1));
+		let rows = max(rows.div_ceil(4) * 4, 4);
+
+		let mut chunks = Vec::new();
+		let mut start = 0;
+
+		while start < height {
+			let end = min(start + rows, height);
+
+			chunks.push((start, end));
+			start = end;
+		}
+
+		chunks
+	}
+
+	// rows of different chunks never share a byte, so the threads can write to the grid at once
+	pub struct SharedGrid<G>(pub *mut G);
+
+	unsafe impl<G: Send> Send for SharedGrid<G> {}
+	unsafe impl<G: Sync> Sync for SharedGrid<G> {}
+
+	// the cell next to the given one in a direction, if it's inside of the grid
+	pub fn neighbour<G: Grid>(grid: &G, x: u64, y: u64, dir: Direction) -> Option<(u64, u64)> {
+		use ::utils::Direction::*;
+
+		match dir {
+			S if y + 1 < grid.height() => Some((x, y + 1)),
+			E if x + 1 < grid.width() => Some((x + 1, y)),
+			N if y > 0 => Some((x, y - 1)),
+			W if x > 0 => Some((x - 1, y)),
+			_ => None
+		}
+	}
+
+	// a step of a random walk, the grid must have more than one cell
+	pub fn random_neighbour<G: Grid, R: Rng>(grid: &G, x: u64, y: u64, rng: &mut R)
+		-> (Direction, (u64, u64)) {
+		let mut candidates = [(Direction::S, (0, 0)); 4];
+		let mut count = 0;
+
+		for &dir in Direction::enumerate() {
+			if let Some(cell) = neighbour(grid, x, y, dir) {
+				candidates[count] = (dir, cell);
+				count += 1;
+			}
+		}
+
+		candidates[(rng.next_f64() * count as f64) as usize]
+	}
+
+	// opens the passage in a direction, passages north and west are kept by the neighbour
+	pub fn carve<G: Grid>(grid: &mut G, x: u64, y: u64, dir: Direction) {
+		use ::utils::Direction::*;
+
+		match dir {
+			S | E => grid.or_set(x, y, dir as u8),
+			N => grid.or_set(x, y - 1, S as u8),
+			W => grid.or_set(x - 1, y, E as u8)
 		}
 	}
 }
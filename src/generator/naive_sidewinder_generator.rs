@@ -2,11 +2,13 @@ use ::{Grid, Generator, GeneratorOption};
 use ::utils::Direction::{S, E};
 use ::lcg_rng::LCGRng;
 use super::utils::init_rng;
+use super::progress::Progress;
 use super::utils::rand::Rng;
 
 pub struct NaiveSidewinderGenerator<'a, G: 'a + Grid> {
 	grid: &'a mut G,
-	rng: LCGRng
+	rng: LCGRng,
+	progress: Progress
 }
 
 impl<'a, G: Grid> NaiveSidewinderGenerator<'a, G> {
@@ -14,14 +16,15 @@ impl<'a, G: Grid> NaiveSidewinderGenerator<'a, G> {
 		let mut seed = None;
 
 		for o in options {
-			match o {
-				&GeneratorOption::Seed(in_seed) => seed = Some(in_seed)
+			if let GeneratorOption::Seed(in_seed) = *o {
+				seed = Some(in_seed);
 			}
 		}
 
 		NaiveSidewinderGenerator {
-			grid: grid,
-			rng: init_rng(seed)
+			grid,
+			rng: init_rng(seed),
+			progress: Progress::from_options(options)
 		}
 	}
 }
@@ -44,6 +47,10 @@ impl<'a, G: Grid> Generator for NaiveSidewinderGenerator<'a, G> {
 					unsafe { grid.or_set_unchecked(x, y, E as u8); }
 				}
 			}
+
+			if !self.progress.advance(grid.width()) {
+				return;
+			}
 		}
 	}
 }
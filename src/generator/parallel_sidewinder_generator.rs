@@ -0,0 +1,69 @@
+extern crate num_cpus;
+
+use ::{Grid, Generator, GeneratorOption};
+use ::lcg_rng::LCGRng;
+use super::sidewinder_generator::carve_row;
+use super::utils::{init_rng, row_rng, row_chunks, SharedGrid};
+use super::progress::Progress;
+use super::utils::rand::Rng;
+
+pub struct ParallelSidewinderGenerator<'a, G: 'a + Grid> {
+	grid: &'a mut G,
+	rng: LCGRng,
+	thread_count: u64,
+	progress: Progress
+}
+
+impl<'a, G: 'a + Grid> ParallelSidewinderGenerator<'a, G> {
+	pub fn new(grid: &'a mut G, options: &[GeneratorOption]) -> Self {
+		let mut seed = None;
+		let mut thread_count = num_cpus::get() as u64;
+
+		for o in options {
+			match *o {
+				GeneratorOption::Seed(in_seed) => seed = Some(in_seed),
+				GeneratorOption::ThreadCount(in_thread_count) => thread_count = in_thread_count,
+
+				_ => {}
+			}
+		}
+
+		ParallelSidewinderGenerator {
+			grid,
+			rng: init_rng(seed),
+			thread_count,
+			progress: Progress::from_options(options)
+		}
+	}
+}
+
+impl<'a, G: 'a + Grid> Generator for ParallelSidewinderGenerator<'a, G> {
+	fn generate(&mut self) {
+		use std::thread;
+
+		// every row gets its own generator, so the maze does not depend on the thread count
+		let base = self.rng.next_u64();
+		let chunks = row_chunks(self.grid.height(), self.thread_count);
+
+		let width = self.grid.width();
+		let progress = &self.progress;
+		let grid = SharedGrid(&mut *self.grid as *mut G);
+		let grid = &grid;
+
+		thread::scope(|scope| {
+			for (start, end) in chunks {
+				scope.spawn(move || {
+					let grid: &mut G = unsafe { &mut *grid.0 };
+
+					for y in start..end {
+						carve_row(grid, y, &mut row_rng(base, y));
+
+						if !progress.advance(width) {
+							return;
+						}
+					}
+				});
+			}
+		});
+	}
+}
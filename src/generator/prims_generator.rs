@@ -0,0 +1,148 @@
+use ::{Grid, Generator, GeneratorOption};
+use ::error::Result;
+use ::utils::Direction;
+use ::utils::Direction::{S, E};
+use ::lcg_rng::LCGRng;
+use super::utils::{init_rng, neighbour, carve};
+use super::progress::Progress;
+use super::slots::Slots;
+use super::utils::rand::Rng;
+
+pub struct PrimsGenerator<'a, G: 'a + Grid> {
+	grid: &'a mut G,
+	rng: LCGRng,
+	frontier: Frontier,
+	progress: Progress
+}
+
+impl<'a, G: Grid> PrimsGenerator<'a, G> {
+	pub fn new(grid: &'a mut G, options: &[GeneratorOption]) -> Result<Self> {
+		let mut seed = None;
+		let mut path = None;
+
+		for o in options {
+			match *o {
+				GeneratorOption::Seed(in_seed) => seed = Some(in_seed),
+				GeneratorOption::FrontierFilePath(ref in_path) => path = Some(in_path.clone()),
+
+				_ => {}
+			}
+		}
+
+		// a cell is queued at most once, so the frontier never holds more than every cell
+		let cells = grid.width() * grid.height();
+
+		Ok(PrimsGenerator {
+			frontier: Frontier {
+				slots: match path {
+					Some(path) => Slots::mapped(cells, path)?,
+					None => Slots::in_memory(cells)
+				},
+				len: 0
+			},
+			grid,
+			rng: init_rng(seed),
+			progress: Progress::from_options(options)
+		})
+	}
+
+	// the start cell is part of the maze before it has any passage
+	fn in_maze(&self, x: u64, y: u64, start: (u64, u64)) -> bool {
+		let mut val = self.grid.get(x, y);
+		if x > 0 { val |= self.grid.get(x - 1, y) & E as u8 };
+		if y > 0 { val |= self.grid.get(x, y - 1) & S as u8 };
+
+		val != 0 || (x, y) == start
+	}
+
+	fn grow(&mut self, sx: u64, sy: u64) {
+		let width = self.grid.width();
+
+		self.add_frontier(sx, sy, (sx, sy));
+
+		while self.frontier.len > 0 {
+			let i = (self.rng.next_f64() * self.frontier.len as f64) as u64;
+			let index = self.frontier.swap_remove(i);
+			let (x, y) = (index % width, index / width);
+
+			// connect the cell to a random neighbour that is part of the maze already
+			let mut candidates = [S; 4];
+			let mut count = 0;
+
+			for &dir in Direction::enumerate() {
+				if let Some((nx, ny)) = neighbour(self.grid, x, y, dir) {
+					if self.in_maze(nx, ny, (sx, sy)) {
+						candidates[count] = dir;
+						count += 1;
+					}
+				}
+			}
+
+			let dir = candidates[(self.rng.next_f64() * count as f64) as usize];
+
+			carve(self.grid, x, y, dir);
+
+			self.add_frontier(x, y, (sx, sy));
+
+			if !self.progress.advance(1) {
+				return;
+			}
+		}
+	}
+
+	// queues the neighbours of a cell that just joined the maze, a neighbour outside of the maze
+	// is queued already if any of its other neighbours is in the maze
+	fn add_frontier(&mut self, x: u64, y: u64, start: (u64, u64)) {
+		let width = self.grid.width();
+
+		for &dir in Direction::enumerate() {
+			if let Some((nx, ny)) = neighbour(self.grid, x, y, dir) {
+				if self.in_maze(nx, ny, start) {
+					continue;
+				}
+
+				let queued = Direction::enumerate().iter()
+					.filter_map(|&dir| neighbour(self.grid, nx, ny, dir))
+					.any(|(ox, oy)| (ox, oy) != (x, y) && self.in_maze(ox, oy, start));
+
+				if !queued {
+					self.frontier.push(ny * width + nx);
+				}
+			}
+		}
+	}
+}
+
+impl<'a, G: Grid> Generator for PrimsGenerator<'a, G> {
+	fn generate(&mut self) {
+		let (width, height) = (self.grid.width(), self.grid.height());
+
+		let x = (self.rng.next_f64() * width as f64) as u64;
+		let y = (self.rng.next_f64() * height as f64) as u64;
+
+		self.grow(x, y);
+		self.frontier.slots.remove_file();
+	}
+}
+
+// the packed indices of the cells next to the maze, in the order they're picked from
+struct Frontier {
+	slots: Slots,
+	len: u64
+}
+
+impl Frontier {
+	fn push(&mut self, index: u64) {
+		self.slots.get_mut()[self.len as usize] = index;
+		self.len += 1;
+	}
+
+	fn swap_remove(&mut self, i: u64) -> u64 {
+		let slots = self.slots.get_mut();
+
+		self.len -= 1;
+		slots.swap(i as usize, self.len as usize);
+
+		slots[self.len as usize]
+	}
+}
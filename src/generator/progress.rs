@@ -0,0 +1,114 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use ::GeneratorOption;
+
+type Callback = dyn Fn(u64, u64) -> bool + Send + Sync;
+
+// Reports how many cells a generator has processed and lets the caller cancel it. The
+// callback gets the processed and the total number of cells every `interval` cells and
+// returns false to cancel, `cancel` does the same from any thread. Clones share their state,
+// so a clone can be kept to cancel a generation that runs elsewhere.
+#[derive(Clone, Default)]
+pub struct Progress(Option<Arc<ProgressState>>);
+
+struct ProgressState {
+	callback: Box<Callback>,
+	interval: u64,
+	processed: AtomicU64,
+	total: AtomicU64,
+	cancelled: AtomicBool
+}
+
+impl Progress {
+	pub fn new<F>(interval: u64, callback: F) -> Self
+		where F: Fn(u64, u64) -> bool + Send + Sync + 'static {
+		Progress(Some(Arc::new(ProgressState {
+			callback: Box::new(callback),
+			interval: interval.max(1),
+			processed: AtomicU64::new(0),
+			total: AtomicU64::new(0),
+			cancelled: AtomicBool::new(false)
+		})))
+	}
+
+	// the hook given in the options, or one that does nothing
+	pub fn from_options(options: &[GeneratorOption]) -> Self {
+		for o in options {
+			if let GeneratorOption::Progress(ref progress) = *o {
+				return progress.clone();
+			}
+		}
+
+		Progress::default()
+	}
+
+	pub fn cancel(&self) {
+		if let Some(ref state) = self.0 {
+			state.cancelled.store(true, Ordering::SeqCst);
+		}
+	}
+
+	pub fn is_cancelled(&self) -> bool {
+		self.0.as_ref().is_some_and(|state| state.cancelled.load(Ordering::SeqCst))
+	}
+
+	pub fn processed(&self) -> u64 {
+		self.0.as_ref().map_or(0, |state| state.processed.load(Ordering::SeqCst))
+	}
+
+	// resets the counters at the start of a generation, a cancel before it is forgotten
+	pub fn start(&self, total: u64) {
+		if let Some(ref state) = self.0 {
+			state.processed.store(0, Ordering::SeqCst);
+			state.total.store(total, Ordering::SeqCst);
+			state.cancelled.store(false, Ordering::SeqCst);
+		}
+	}
+
+	// counts processed cells, generators call it at least once per cell they finish and stop
+	// as soon as it returns false, with 0 it only checks for a cancel
+	#[inline]
+	pub fn advance(&self, cells: u64) -> bool {
+		match self.0 {
+			Some(ref state) => {
+				let before = state.processed.fetch_add(cells, Ordering::Relaxed);
+				let after = before + cells;
+
+				if before / state.interval != after / state.interval
+					&& !(state.callback)(after, state.total.load(Ordering::Relaxed)) {
+					state.cancelled.store(true, Ordering::SeqCst);
+				}
+
+				!state.cancelled.load(Ordering::Relaxed)
+			},
+			None => true
+		}
+	}
+
+	// calls the callback with the current counts, for generators that can work for long
+	// without finishing a cell, returns false if the generation was cancelled
+	pub fn poll(&self) -> bool {
+		match self.0 {
+			Some(ref state) => {
+				let processed = state.processed.load(Ordering::Relaxed);
+
+				if !(state.callback)(processed, state.total.load(Ordering::Relaxed)) {
+					state.cancelled.store(true, Ordering::SeqCst);
+				}
+
+				!state.cancelled.load(Ordering::Relaxed)
+			},
+			None => true
+		}
+	}
+
+	// reports the end of a generation that wasn't cancelled
+	pub fn finish(&self) {
+		if let Some(ref state) = self.0 {
+			let total = state.total.load(Ordering::SeqCst);
+
+			state.processed.store(total, Ordering::SeqCst);
+			(state.callback)(total, total);
+		}
+	}
+}
@@ -1,12 +1,13 @@
-extern crate rand;
-
 use ::lcg_rng::LCGRng;
 use ::{Grid, Generator, GeneratorOption};
-use self::rand::{Rng, SeedableRng};
+use super::utils::init_rng;
+use super::progress::Progress;
+use super::utils::rand::Rng;
 
 pub struct RecursiveBacktrackGenerator<'a, G: 'a + Grid> {
 	grid: &'a mut G,
-	rng: LCGRng
+	rng: LCGRng,
+	progress: Progress
 }
 
 impl<'a, G: Grid> RecursiveBacktrackGenerator<'a, G> {
@@ -14,19 +15,15 @@ impl<'a, G: Grid> RecursiveBacktrackGenerator<'a, G> {
 		let mut seed = None;
 
 		for o in options {
-			match o {
-				&GeneratorOption::Seed(in_seed) => seed = Some(in_seed)
+			if let GeneratorOption::Seed(in_seed) = *o {
+				seed = Some(in_seed);
 			}
 		}
 
 		RecursiveBacktrackGenerator {
-			grid: grid,
-			rng: if let Some(s) = seed {
-//TODO: revise this hackish thing
-					LCGRng::from_seed((s[0] as u64) + (s[1] as u64) << 32)
-				} else {
-					LCGRng::new_unseeded()
-				}
+			grid,
+			rng: init_rng(seed),
+			progress: Progress::from_options(options)
 		}
 	}
 
@@ -38,6 +35,11 @@ impl<'a, G: Grid> RecursiveBacktrackGenerator<'a, G> {
 		let direction_offset = (self.rng.next_f64() * directions.len() as f64) as usize;
 
 		for i in 0..4 {
+			// every frame up the recursion has to stop carving once it's cancelled
+			if self.progress.is_cancelled() {
+				return;
+			}
+
 			let dir = directions[(direction_offset + i * 3) % directions.len()];
 			let (nx, ny) = match dir {
 				S => (x, y + 1),
@@ -59,6 +61,7 @@ impl<'a, G: Grid> RecursiveBacktrackGenerator<'a, G> {
 						self.grid.or_set(nx as u64, ny as u64, dir.opposite() as u8);
 					}
 
+					self.progress.advance(1);
 					self.recursive_carve(nx, ny);
 				}
 			}
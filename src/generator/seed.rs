@@ -0,0 +1,96 @@
+use std::convert::From;
+
+// Seeds the generators. A seed is 128 bits wide, u64 and u128 seeds are taken as they are and
+// strings and bytes are hashed (FNV-1a), so any of them can be given at run time.
+//
+// The generators keep 64 bits of state, the high half of a seed is mixed into the low half, so
+// every u64 seed gives a different maze and a u64 seed gives the same maze as the u128 one of
+// the same value.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Seed(u128);
+
+const FNV_OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+const FNV_PRIME: u128 = 0x0000000001000000000000000000013B;
+
+impl Seed {
+	// the encoding of the seed words generators used to have, the words are added up before the
+	// sum is shifted into the upper half, so many of them collide, kept to reproduce the mazes
+	// generated with it
+	pub fn legacy(words: &[u32]) -> Seed {
+		let word = |i| words.get(i).cloned().unwrap_or(0) as u64;
+
+		Seed::from((word(0) + word(1)) << 32)
+	}
+
+	pub fn from_bytes(bytes: &[u8]) -> Seed {
+		let mut hash = FNV_OFFSET_BASIS;
+
+		for &byte in bytes {
+			hash ^= byte as u128;
+			hash = hash.wrapping_mul(FNV_PRIME);
+		}
+
+		Seed(hash)
+	}
+
+	// up to 4 words, least significant first, missing words are 0, None if there are more
+	pub fn from_words(words: &[u32]) -> Option<Seed> {
+		if words.len() > 4 {
+			return None;
+		}
+
+		Some(Seed(words.iter().rev().fold(0, |value, &word| value << 32 | word as u128)))
+	}
+
+	pub fn to_words(self) -> [u32; 4] {
+		let mut words = [0; 4];
+
+		for (i, word) in words.iter_mut().enumerate() {
+			*word = (self.0 >> (i * 32)) as u32;
+		}
+
+		words
+	}
+
+	pub fn value(self) -> u128 {
+		self.0
+	}
+
+	// the state the random number generators start from
+	pub fn rng_state(self) -> u64 {
+		(self.0 as u64) ^ mix((self.0 >> 64) as u64)
+	}
+}
+
+// the MurmurHash3 finalizer, maps 0 to 0
+fn mix(mut z: u64) -> u64 {
+	z ^= z >> 33;
+	z = z.wrapping_mul(0xff51afd7ed558ccd);
+	z ^= z >> 33;
+	z = z.wrapping_mul(0xc4ceb9fe1a85ec53);
+	z ^ (z >> 33)
+}
+
+impl From<u64> for Seed {
+	fn from(seed: u64) -> Seed {
+		Seed(seed as u128)
+	}
+}
+
+impl From<u128> for Seed {
+	fn from(seed: u128) -> Seed {
+		Seed(seed)
+	}
+}
+
+impl<'a> From<&'a str> for Seed {
+	fn from(seed: &'a str) -> Seed {
+		Seed::from_bytes(seed.as_bytes())
+	}
+}
+
+impl From<String> for Seed {
+	fn from(seed: String) -> Seed {
+		Seed::from_bytes(seed.as_bytes())
+	}
+}
@@ -1,85 +1,62 @@
-extern crate num_cpus;
-
 use ::{Grid, Generator, GeneratorOption};
 use ::utils::Direction::{S, E};
 use ::lcg_rng::LCGRng;
-use super::utils::init_rng;
-use super::utils::rand::{Rng, SeedableRng};
+use super::utils::{init_rng, row_rng};
+use super::progress::Progress;
+use super::utils::rand::Rng;
 
-pub struct SidewinderGenerator<'a, G: 'a + Grid + Send + Sync> {
+pub struct SidewinderGenerator<'a, G: 'a + Grid> {
 	grid: &'a mut G,
-	rng: LCGRng
+	rng: LCGRng,
+	progress: Progress
 }
 
-impl<'a, G: 'a + Grid + Send + Sync> SidewinderGenerator<'a, G> {
+impl<'a, G: Grid> SidewinderGenerator<'a, G> {
 	pub fn new(grid: &'a mut G, options: &[GeneratorOption]) -> Self {
-		use GeneratorOption::*;
-
 		let mut seed = None;
 
 		for o in options {
-			match o {
-				&GeneratorOption::Seed(in_seed) => seed = Some(in_seed)
+			if let GeneratorOption::Seed(in_seed) = *o {
+				seed = Some(in_seed);
 			}
 		}
 
 		SidewinderGenerator {
-			grid: grid,
-			rng: init_rng(seed)
+			grid,
+			rng: init_rng(seed),
+			progress: Progress::from_options(options)
 		}
 	}
 }
 
-impl<'a, G: 'a + Grid + Send + Sync> Generator for SidewinderGenerator<'a, G> {
+impl<'a, G: Grid> Generator for SidewinderGenerator<'a, G> {
 	fn generate(&mut self) {
-		#![allow(mutable_transmutes)]
-		use std::thread;
-		use std::sync::Arc;
-		use std::mem::transmute;
-		use self::num_cpus;
-
-		let num_cores = num_cpus::get() as u64;
-		let rows_per_thread = self.grid.height() / num_cores;
-
-		assert!(self.grid.height() % num_cores == 0,
-			"Maze height must be divisible by {}, but {} given", num_cores, self.grid.height());
-
-		let grid = Arc::new(&mut *self.grid);
-		let mut rng = self.rng.clone();
-
-        let mut threads = Vec::with_capacity(num_cores as usize);
+		let base = self.rng.next_u64();
 
-		for tn in 0..num_cores {
-			let seed = !rng.next_u64();
-			rng.reseed(seed);
+		for y in 0..self.grid.height() {
+			carve_row(&mut *self.grid, y, &mut row_rng(base, y));
 
-			let grid = grid.clone();
-			let mut rng = rng.clone();
-
-			threads.push(thread::scoped(move || {
-				let grid: &mut G = unsafe { transmute(&**grid) };
+			if !self.progress.advance(self.grid.width()) {
+				return;
+			}
+		}
+	}
+}
 
-				for y in ((tn * rows_per_thread)..((tn + 1) * rows_per_thread)).rev() {
-					let mut run_start = 0;
+// carves the passages of a single row, the bottom row ends up being one long corridor
+pub fn carve_row<G: Grid>(grid: &mut G, y: u64, rng: &mut LCGRng) {
+	let mut run_start = 0;
 
-					for x in 0..grid.width() {
-						if y < grid.height() - 1
-							&& (x + 1 == grid.width() || rng.next_f64() > 0.50) {
-							let carve_point =
-								run_start + (rng.next_f64() * (x - run_start + 1) as f64) as u64;
+	for x in 0..grid.width() {
+		if y < grid.height() - 1
+			&& (x + 1 == grid.width() || rng.next_f64() > 0.50) {
+			let carve_point =
+				run_start + (rng.next_f64() * (x - run_start + 1) as f64) as u64;
 
-							unsafe { grid.or_set_unchecked(carve_point, y, S as u8); }
-							run_start = x + 1;
-						} else if x + 1 < grid.width() {
-							unsafe { grid.or_set_unchecked(x, y, E as u8); }
-						}
-					}
-				}
-			}));
+			unsafe { grid.or_set_unchecked(carve_point, y, S as u8); }
+			run_start = x + 1;
+		} else if x + 1 < grid.width() {
+			unsafe { grid.or_set_unchecked(x, y, E as u8); }
 		}
-
-		for thread in threads {
-            thread.join();
-        }
 	}
 }
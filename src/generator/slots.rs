@@ -0,0 +1,60 @@
+extern crate memmap2;
+
+use ::error::Result;
+use self::memmap2::MmapMut;
+use std::fs::{self, OpenOptions};
+use std::mem::size_of;
+use std::slice;
+
+// a fixed number of u64 slots, zeroed when created, kept in memory or in a memory mapped side
+// file for grids that don't fit in memory, a new side file stays sparse until it's written to
+pub struct Slots {
+	storage: Storage,
+	len: usize
+}
+
+enum Storage {
+	Memory(Vec<u64>),
+	Mapped(MmapMut, String)
+}
+
+impl Slots {
+	pub fn in_memory(len: u64) -> Self {
+		Slots {
+			storage: Storage::Memory(vec![0; len as usize]),
+			len: len as usize
+		}
+	}
+
+	pub fn mapped(len: u64, path: String) -> Result<Self> {
+		let file = OpenOptions::new()
+			.create(true)
+			.truncate(true)
+			.read(true)
+			.write(true)
+			.open(&path)?;
+
+		file.set_len(len * size_of::<u64>() as u64)?;
+
+		Ok(Slots {
+			storage: Storage::Mapped(unsafe { MmapMut::map_mut(&file)? }, path),
+			len: len as usize
+		})
+	}
+
+	pub fn get_mut(&mut self) -> &mut [u64] {
+		match self.storage {
+			Storage::Memory(ref mut slots) => slots,
+			// mappings are page aligned so the slots are always aligned
+			Storage::Mapped(ref mut mmap, _) => unsafe {
+				slice::from_raw_parts_mut(mmap.as_mut_ptr() as *mut u64, self.len)
+			}
+		}
+	}
+
+	pub fn remove_file(&mut self) {
+		if let Storage::Mapped(_, ref path) = self.storage {
+			let _ = fs::remove_file(path);
+		}
+	}
+}
@@ -1,11 +1,25 @@
-use ::{Grid, Generator, GeneratorOption};
+extern crate byteorder;
+
+use std::io::Read;
+use ::{Grid, Generator, GeneratorOption, GeneratorType};
+use ::error::{Error, Result};
 use ::lcg_rng::LCGRng;
 use super::utils::init_rng;
+use super::progress::Progress;
+use super::checkpoint::{Checkpoint, JournaledGrid};
 use super::utils::rand::Rng;
+use self::byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
+// a cell on the stack, the next of its directions to try and where its random order of
+// directions starts, drawn once when the cell is pushed
+type Frame = (i64, i64, (usize, usize));
 
 pub struct StackBacktrackGenerator<'a, G: 'a + Grid> {
 	grid: &'a mut G,
-	rng: LCGRng
+	rng: LCGRng,
+	progress: Progress,
+	checkpoint: Option<Checkpoint>,
+	error: Option<Error>
 }
 
 impl<'a, G: Grid> StackBacktrackGenerator<'a, G> {
@@ -13,66 +27,181 @@ impl<'a, G: Grid> StackBacktrackGenerator<'a, G> {
 		let mut seed = None;
 
 		for o in options {
-			match o {
-				&GeneratorOption::Seed(in_seed) => seed = Some(in_seed)
+			if let GeneratorOption::Seed(in_seed) = *o {
+				seed = Some(in_seed);
 			}
 		}
 
 		StackBacktrackGenerator {
-			grid: grid,
-			rng: init_rng(seed)
+			grid,
+			rng: init_rng(seed),
+			progress: Progress::from_options(options),
+			checkpoint: Checkpoint::from_options(options),
+			error: None
+		}
+	}
+
+	// the grid only sees the changes up to the last checkpoint, the ones after it are kept
+	// aside and saved along with the stack
+	fn resumable_carve(&mut self, mut checkpoint: Checkpoint) -> Result<()> {
+		let mut grid = JournaledGrid::new(&mut *self.grid);
+		let mut stack = start_stack(&mut self.rng);
+		let mut carved = 0;
+
+		if let Some(mut resumed) = checkpoint.load(grid.grid(), GeneratorType::StackBacktrack)? {
+			self.rng = resumed.rng;
+			carved = resumed.state.read_u64::<LittleEndian>()?;
+			stack = read_stack(&mut resumed.state, grid.grid())?;
+			grid.read_changes(&mut resumed.state)?;
+			grid.apply();
+
+			self.progress.advance(carved);
+		}
+
+		let mut error = None;
+
+		stack_carve(&mut grid, &mut self.rng, &self.progress, &mut stack, |grid, rng, stack| {
+			carved += 1;
+
+			if checkpoint.advance(1) {
+				if let Err(e) = save(&checkpoint, grid, rng, carved, stack) {
+					error = Some(e);
+					return false;
+				}
+			}
+
+			true
+		});
+
+		if let Some(e) = error {
+			return Err(e);
 		}
+
+		// a cancelled generation can be resumed, a finished one is saved once more so that
+		// a crash while its last changes are applied isn't mistaken for a fresh start
+		save(&checkpoint, &mut grid, &self.rng, carved, &stack)?;
+
+		if !self.progress.is_cancelled() {
+			grid.flush()?;
+			checkpoint.remove()?;
+		}
+
+		Ok(())
 	}
+}
+
+fn stack_carve<J, F>(grid: &mut J, rng: &mut LCGRng, progress: &Progress, stack: &mut Vec<Frame>,
+	mut carved: F) where J: Grid, F: FnMut(&mut J, &LCGRng, &[Frame]) -> bool {
+	use ::utils::Direction;
+	use ::utils::Direction::*;
+
+	let directions = Direction::enumerate();
+
+	// a direction that was skipped leads to a visited cell, so a cell resumes where it left
+	// off in the same order when it's back on top of the stack
+	'stack_loop: while let Some((x, y, (i, direction_offset))) = stack.pop() {
+		for i in i..4 {
+			let dir = directions[(direction_offset + i * 3) % directions.len()];
+			let (nx, ny) = match dir {
+				S => (x, y + 1),
+				E => (x + 1, y),
+				N => (x, y - 1),
+				W => (x - 1, y)
+			};
+
+			if nx >= 0 && (nx as u64) < grid.width()
+				&& ny >= 0 && (ny as u64) < grid.height() {
+				let mut val = grid.get(nx as u64, ny as u64);
+				if nx > 0 { val |= grid.get((nx - 1) as u64, ny as u64) & E as u8 };
+				if ny > 0 { val |= grid.get(nx as u64, (ny - 1) as u64) & S as u8 };
+
+				if val == 0 {
+					if dir == S || dir == E {
+						grid.or_set(x as u64, y as u64, dir as u8);
+					} else {
+						grid.or_set(nx as u64, ny as u64, dir.opposite() as u8);
+					}
 
-	fn stack_carve(&mut self, sx: i64, sy: i64) {
-		use ::utils::Direction;
-		use ::utils::Direction::*;
-
-		let directions = Direction::enumerate();
-
-		let mut stack = Vec::new();
-		stack.push((sx, sy, (0,)));
-
-		'stack_loop: while !stack.is_empty() {
-			let (x, y, (i,)) = stack.pop().unwrap();
-
-			let direction_offset = (self.rng.next_f64() * directions.len() as f64) as usize;
-
-			for i in i..4 {
-				let dir = directions[(direction_offset + i * 3) % directions.len()];
-				let (nx, ny) = match dir {
-					S => (x, y + 1),
-					E => (x + 1, y),
-					N => (x, y - 1),
-					W => (x - 1, y)
-				};
-
-				if nx >= 0 && (nx as u64) < self.grid.width()
-					&& ny >= 0 && (ny as u64) < self.grid.height() {
-					let mut val = self.grid.get(nx as u64, ny as u64);
-					if nx > 0 { val |= self.grid.get((nx - 1) as u64, ny as u64) & E as u8 };
-					if ny > 0 { val |= self.grid.get(nx as u64, (ny - 1) as u64) & S as u8 };
-					
-					if val == 0 {
-						if dir == S || dir == E {
-							self.grid.or_set(x as u64, y as u64, dir as u8);
-						} else {
-							self.grid.or_set(nx as u64, ny as u64, dir.opposite() as u8);
-						}
-
-						stack.push((x, y, (i + 1,)));
-						stack.push((nx, ny, (0,)));
-
-						continue 'stack_loop;
+					stack.push((x, y, (i + 1, direction_offset)));
+					stack.push((nx, ny, (0, random_offset(rng))));
+
+					let saved = carved(grid, rng, stack);
+
+					if !progress.advance(1) || !saved {
+						return;
 					}
+
+					continue 'stack_loop;
 				}
 			}
 		}
 	}
 }
 
+fn start_stack(rng: &mut LCGRng) -> Vec<Frame> {
+	vec![(0, 0, (0, random_offset(rng)))]
+}
+
+fn random_offset(rng: &mut LCGRng) -> usize {
+	(rng.next_f64() * 4.0) as usize
+}
+
+fn save<G: Grid>(checkpoint: &Checkpoint, grid: &mut JournaledGrid<G>, rng: &LCGRng, carved: u64,
+	stack: &[Frame]) -> Result<()> {
+	let mut state = Vec::with_capacity(16 + stack.len() * 18);
+
+	state.write_u64::<LittleEndian>(carved)?;
+	state.write_u64::<LittleEndian>(stack.len() as u64)?;
+
+	for &(x, y, (i, direction_offset)) in stack {
+		state.write_i64::<LittleEndian>(x)?;
+		state.write_i64::<LittleEndian>(y)?;
+		state.write_u8(i as u8)?;
+		state.write_u8(direction_offset as u8)?;
+	}
+
+	grid.write_changes(&mut state)?;
+	checkpoint.save(grid, GeneratorType::StackBacktrack, rng, &state)?;
+	grid.apply();
+
+	Ok(())
+}
+
+fn read_stack<R: Read, G: Grid>(input: &mut R, grid: &G) -> Result<Vec<Frame>> {
+	let len = input.read_u64::<LittleEndian>()?;
+	let mut stack = Vec::new();
+
+	for _ in 0..len {
+		let x = input.read_i64::<LittleEndian>()?;
+		let y = input.read_i64::<LittleEndian>()?;
+		let i = input.read_u8()? as usize;
+		let direction_offset = input.read_u8()? as usize;
+
+		if x < 0 || x as u64 >= grid.width() || y < 0 || y as u64 >= grid.height() || i > 4
+			|| direction_offset > 3 {
+			return Err(Error::InvalidFormat(String::from("the checkpoint has an invalid stack")));
+		}
+
+		stack.push((x, y, (i, direction_offset)));
+	}
+
+	Ok(stack)
+}
+
 impl<'a, G: Grid> Generator for StackBacktrackGenerator<'a, G> {
 	fn generate(&mut self) {
-		self.stack_carve(0, 0);
+		match self.checkpoint.take() {
+			Some(checkpoint) => if let Err(e) = self.resumable_carve(checkpoint) {
+				self.error = Some(e);
+			},
+			None => {
+				let mut stack = start_stack(&mut self.rng);
+				stack_carve(self.grid, &mut self.rng, &self.progress, &mut stack, |_, _, _| true);
+			}
+		}
+	}
+
+	fn take_error(&mut self) -> Option<Error> {
+		self.error.take()
 	}
 }
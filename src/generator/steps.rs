@@ -0,0 +1,138 @@
+// Runs a generator on its own thread against a grid that reports every change it makes.
+// The changes are handed over through a channel without a buffer, so the generator only
+// advances as far as the steps are consumed.
+
+use std::sync::mpsc::{sync_channel, SyncSender, Receiver};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::panic;
+use ::{Grid, GeneratorType, GeneratorOption, generate};
+use ::error::Result;
+use ::utils::Direction;
+use ::utils::Direction::{S, E};
+
+// passages are always reported from the cell that keeps them, north and west passages show
+// up as south and east ones of the neighbouring cell
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Step {
+	// the passage from the cell in the direction was opened
+	Carve(u64, u64, Direction),
+	// the passage from the cell in the direction was closed
+	Wall(u64, u64, Direction),
+	// every byte of the grid was set to the value, 0xFF opens every passage
+	Fill(u8)
+}
+
+pub struct Steps<G: Grid> {
+	receiver: Receiver<Step>,
+	handle: JoinHandle<Result<G>>
+}
+
+impl<G: Grid + 'static> Steps<G> {
+	pub fn new(grid: G, generator_type: GeneratorType, options: &[GeneratorOption]) -> Self {
+		let (sender, receiver) = sync_channel(0);
+		let options = options.to_vec();
+
+		let handle = thread::spawn(move || {
+			let mut grid = RecordingGrid {
+				grid,
+				sender,
+				connected: AtomicBool::new(true)
+			};
+
+			generate(&mut grid, generator_type, &options)?;
+
+			Ok(grid.grid)
+		});
+
+		Steps {
+			receiver,
+			handle
+		}
+	}
+
+	// runs the rest of the generation without stepping and returns the grid
+	pub fn into_grid(self) -> Result<G> {
+		drop(self.receiver);
+
+		match self.handle.join() {
+			Ok(grid) => grid,
+			Err(panic) => panic::resume_unwind(panic)
+		}
+	}
+}
+
+impl<G: Grid> Iterator for Steps<G> {
+	type Item = Step;
+
+	fn next(&mut self) -> Option<Step> {
+		self.receiver.recv().ok()
+	}
+}
+
+struct RecordingGrid<G: Grid> {
+	grid: G,
+	sender: SyncSender<Step>,
+	// cleared once the steps are dropped, the generator then runs on without reporting
+	connected: AtomicBool
+}
+
+impl<G: Grid> RecordingGrid<G> {
+	fn send(&self, step: Step) {
+		if self.connected.load(Ordering::Relaxed) && self.sender.send(step).is_err() {
+			self.connected.store(false, Ordering::Relaxed);
+		}
+	}
+
+	// reports the passages of a cell that changed from `old`
+	fn record(&self, x: u64, y: u64, old: u8) {
+		let new = unsafe { self.grid.get_unchecked(x, y) };
+
+		for &dir in &[S, E] {
+			if (new ^ old) & dir as u8 != 0 {
+				self.send(if new & dir as u8 != 0 { Step::Carve(x, y, dir) } else { Step::Wall(x, y, dir) });
+			}
+		}
+	}
+}
+
+impl<G: Grid> Grid for RecordingGrid<G> {
+	fn width(&self) -> u64 {
+		self.grid.width()
+	}
+
+	fn height(&self) -> u64 {
+		self.grid.height()
+	}
+
+	fn fill(&mut self, fill: u8) {
+		self.grid.fill(fill);
+		self.send(Step::Fill(fill));
+	}
+
+	unsafe fn get_unchecked(&self, x: u64, y: u64) -> u8 {
+		self.grid.get_unchecked(x, y)
+	}
+
+	unsafe fn set_unchecked(&mut self, x: u64, y: u64, value: u8) {
+		let old = self.grid.get_unchecked(x, y);
+		self.grid.set_unchecked(x, y, value);
+		self.record(x, y, old);
+	}
+
+	unsafe fn or_set_unchecked(&mut self, x: u64, y: u64, value: u8) {
+		let old = self.grid.get_unchecked(x, y);
+		self.grid.or_set_unchecked(x, y, value);
+		self.record(x, y, old);
+	}
+
+	unsafe fn unset_provided_unchecked(&mut self, x: u64, y: u64, value: u8) {
+		let old = self.grid.get_unchecked(x, y);
+		self.grid.unset_provided_unchecked(x, y, value);
+		self.record(x, y, old);
+	}
+
+	fn flush(&self) -> Result<()> {
+		self.grid.flush()
+	}
+}
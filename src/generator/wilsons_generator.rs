@@ -0,0 +1,117 @@
+use ::{Grid, Generator, GeneratorOption, PackedOption};
+use ::bitset::BitSet;
+use ::grid::in_memory_packed_grid::InMemoryPackedGrid;
+use ::utils::Direction;
+use ::lcg_rng::LCGRng;
+use super::aldous_broder_generator::{random_walk, WALK_STEPS_PER_CHECK};
+use super::utils::{init_rng, neighbour, random_neighbour, carve};
+use super::progress::Progress;
+
+pub struct WilsonsGenerator<'a, G: 'a + Grid> {
+	grid: &'a mut G,
+	rng: LCGRng,
+	// share of the cells visited by an Aldous-Broder walk before switching to loop-erased walks
+	aldous_broder_share: f64,
+	progress: Progress
+}
+
+impl<'a, G: Grid> WilsonsGenerator<'a, G> {
+	pub fn new(grid: &'a mut G, options: &[GeneratorOption]) -> Self {
+		let mut seed = None;
+
+		for o in options {
+			if let GeneratorOption::Seed(in_seed) = *o {
+				seed = Some(in_seed);
+			}
+		}
+
+		WilsonsGenerator {
+			grid,
+			rng: init_rng(seed),
+			aldous_broder_share: 0.0,
+			progress: Progress::from_options(options)
+		}
+	}
+
+	// Wilson's first walks are long as they have to hit a single cell, while Aldous-Broder
+	// covers the first cells quickly but takes long to find the last ones, so the hybrid
+	// starts out with Aldous-Broder and hands the visited cells over to Wilson's
+	pub fn hybrid(grid: &'a mut G, options: &[GeneratorOption]) -> Self {
+		let mut generator = WilsonsGenerator::new(grid, options);
+		generator.aldous_broder_share = 1.0 / 3.0;
+
+		generator
+	}
+
+	fn loop_erased_walks(&mut self, visited: &mut BitSet) {
+		let (width, height) = (self.grid.width(), self.grid.height());
+
+		// the direction each cell was last left in, as an index into Direction::enumerate(),
+		// overwriting it when a walk crosses itself erases the loop
+		let mut exits = InMemoryPackedGrid::new(&[
+			PackedOption::Width(width),
+			PackedOption::Height(height)
+		]).expect("the exits have the same dimensions as the grid");
+
+		for start in 0..(width * height) {
+			if visited.contains(start) {
+				continue;
+			}
+
+			let (mut x, mut y) = (start % width, start / width);
+			let mut steps = 0u64;
+
+			while !visited.contains(y * width + x) {
+				let (dir, (nx, ny)) = random_neighbour(self.grid, x, y, &mut self.rng);
+
+				steps += 1;
+
+				if steps.is_multiple_of(WALK_STEPS_PER_CHECK) && !self.progress.poll() {
+					return;
+				}
+
+				exits.set(x, y, index_of(dir));
+				x = nx;
+				y = ny;
+			}
+
+			// carve the loop-erased path into the maze
+			let (mut x, mut y) = (start % width, start / width);
+
+			while !visited.contains(y * width + x) {
+				let dir = Direction::enumerate()[exits.get(x, y) as usize];
+
+				carve(self.grid, x, y, dir);
+				visited.insert(y * width + x);
+
+				if !self.progress.advance(1) {
+					return;
+				}
+
+				let (nx, ny) = neighbour(self.grid, x, y, dir).unwrap();
+				x = nx;
+				y = ny;
+			}
+		}
+	}
+}
+
+impl<'a, G: Grid> Generator for WilsonsGenerator<'a, G> {
+	fn generate(&mut self) {
+		let cells = self.grid.width() * self.grid.height();
+		let target = ((cells as f64 * self.aldous_broder_share) as u64).max(1);
+
+		let mut visited = BitSet::new(cells);
+
+		// with a target of a single cell the walk only picks the root of the maze
+		random_walk(self.grid, &mut self.rng, &self.progress, &mut visited, target);
+
+		if !self.progress.is_cancelled() {
+			self.loop_erased_walks(&mut visited);
+		}
+	}
+}
+
+fn index_of(dir: Direction) -> u8 {
+	Direction::enumerate().iter().position(|&d| d == dir).unwrap() as u8
+}
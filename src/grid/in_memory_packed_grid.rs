@@ -1,4 +1,5 @@
-use ::{Grid, PackedOption};
+use ::{Grid, PackedGrid, PackedOption};
+use ::error::{Error, Result};
 use ::utils::*;
 
 pub struct InMemoryPackedGrid {
@@ -11,7 +12,7 @@ unsafe impl Sync for InMemoryPackedGrid {}
 unsafe impl Send for InMemoryPackedGrid {}
 
 impl InMemoryPackedGrid {
-	pub fn new(options: &[PackedOption]) -> Self {
+	pub fn new(options: &[PackedOption]) -> Result<Self> {
 
 		let mut width = 0;
 		let mut height = 0;
@@ -25,14 +26,16 @@ impl InMemoryPackedGrid {
 			}
 		}
 
-		let len = (width * height) as usize;
-		assert!(len % 4 == 0, "area must be divisible by 4, {} given", len);
+		let area = match width.checked_mul(height) {
+			Some(area) if area > 0 => area,
+			_ => return Err(Error::InvalidDimensions(width, height))
+		};
 
-		InMemoryPackedGrid {
-			arr: vec![0; len / 4],
-			width: width,
-			height: height
-		}
+		Ok(InMemoryPackedGrid {
+			arr: vec![0; packed_len(area) as usize],
+			width,
+			height
+		})
 	}
 
 	unsafe fn get_unpacked_unchecked(&self, x: u64, y: u64) -> &u8 {
@@ -45,6 +48,12 @@ impl InMemoryPackedGrid {
 	}
 }
 
+impl PackedGrid for InMemoryPackedGrid {
+	fn new(options: &[PackedOption]) -> Result<Self> {
+		InMemoryPackedGrid::new(options)
+	}
+}
+
 impl Grid for InMemoryPackedGrid {
 
 	#[inline(always)]
@@ -61,6 +70,10 @@ impl Grid for InMemoryPackedGrid {
 		for val in self.arr.iter_mut() {
 			*val = fill;
 		}
+
+		if let Some(last) = self.arr.last_mut() {
+			*last &= trailing_mask(self.width * self.height);
+		}
 	}
 
 	#[inline(always)]
@@ -1,16 +1,15 @@
 use std::marker::PhantomData;
-use std::mem::transmute;
 
 pub struct CArray<T: Sized> (PhantomData<T>);
 
 impl<T: Sized> CArray<T> {
 	#[inline]
 	pub unsafe fn get_unchecked(&self, index: usize) -> &T {
-		transmute(transmute::<&Self, *const T>(self).offset(index as isize))
+		&*(self as *const Self as *const T).add(index)
 	}
 
 	#[inline]
 	pub unsafe fn get_unchecked_mut(&mut self, index: usize) -> &mut T {
-		transmute(transmute::<&mut Self, *mut T>(self).offset(index as isize))
+		&mut *(self as *mut Self as *mut T).add(index)
 	}
 }
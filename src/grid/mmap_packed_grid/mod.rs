@@ -1,121 +1,400 @@
-extern crate mmap;
-extern crate libc;
-extern crate byteorder;
+// Grids are stored in a file that is memory mapped as a whole.
+//
+// Layout of format version 2, all integers are little endian:
+//
+//   offset  size  field
+//        0     8  magic, the bytes "MAZEGRID"
+//        8     4  format version, 2
+//       12     4  flags, see the FLAG_ constants
+//       16     8  width
+//       24     8  height
+//       32     4  generator, the GeneratorType the maze was generated with
+//       36     4  reserved, always 0
+//       40    16  seed, least significant word first
+//       56     4  CRC-32 of the cells, only valid when FLAG_CHECKSUM is set
+//       60     4  CRC-32 of the preceding 60 bytes
+//       64        cells, 4 to a byte starting at the lowest bits, row by row, unused bits
+//                 of the last byte are 0
+//
+// The legacy layout (version 1) is a native endian width and height followed by the cells,
+// such files are still read and written but can't hold anything else.
 
-use ::{Grid, PackedOption};
+extern crate memmap2;
+
+use ::{Grid, GeneratorType, PackedGrid, PackedOption};
+use ::generator::Seed;
+use ::crc32::Crc32;
+use ::error::{Error, Result};
 use ::utils::*;
-use self::mmap::*;
+use self::memmap2::MmapMut;
 use self::carray::*;
-use self::byteorder::{NativeEndian, WriteBytesExt};
 use std::fs::{OpenOptions, File};
+use std::mem::size_of;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 mod carray;
 
+pub const MAGIC: &[u8; 8] = b"MAZEGRID";
+pub const FORMAT_VERSION: u32 = 2;
+
+// the cell checksum is up to date
+pub const FLAG_CHECKSUM: u32 = 1 << 0;
+// the generator and seed fields are filled in
+pub const FLAG_GENERATOR: u32 = 1 << 1;
+pub const FLAG_SEED: u32 = 1 << 2;
+
+const NO_GENERATOR: u32 = 0xFFFFFFFF;
+
 #[repr(C)]
-struct RawPackedGrid {
+struct RawHeader {
+	magic: [u8; 8],
+	version: u32,
+	flags: u32,
 	width: u64,
 	height: u64,
+	generator: u32,
+	reserved: u32,
+	seed: [u32; 4],
+	data_crc: u32,
+	header_crc: u32
+}
 
-	arr: CArray<u8>
+#[repr(C)]
+struct LegacyRawHeader {
+	width: u64,
+	height: u64
 }
 
 pub struct MMAPPackedGrid {
-	raw: *mut RawPackedGrid,
+	// None for files in the legacy layout
+	header: Option<*mut RawHeader>,
+	arr: *mut CArray<u8>,
+
+	width: u64,
+	height: u64,
+	// the cells changed since the checksum was last computed, atomic because the parallel
+	// generators write cells from several threads at once
+	dirty: AtomicBool,
 
 	_file: File,
-	_mmap: MemoryMap
+	mmap: MmapMut
 }
 
 unsafe impl Sync for MMAPPackedGrid {}
 unsafe impl Send for MMAPPackedGrid {}
 
+fn cells_len(width: u64, height: u64) -> Option<u64> {
+	width.checked_mul(height).map(packed_len)
+}
+
 impl MMAPPackedGrid {
-	pub fn new(options: &[PackedOption]) -> Self {
+	pub fn new(options: &[PackedOption]) -> Result<Self> {
 		use std::env;
-		use std::path::Path;
-		use std::mem::size_of;
+		use std::path::PathBuf;
+		use std::io::Write;
 
-		let cwd = env::current_dir().unwrap();
-		let mut bin_path = cwd.as_path();
+		let mut bin_path = env::current_dir()?;
 		let mut width = 0;
 		let mut height = 0;
-		let len;
 
 		for o in options {
 			match *o {
-				PackedOption::MMAPFilePath(ref in_bin_path) => bin_path = Path::new(in_bin_path),
+				PackedOption::MMAPFilePath(ref in_bin_path) => bin_path = PathBuf::from(in_bin_path),
 				PackedOption::Width(in_width) => width = in_width,
 				PackedOption::Height(in_height) => height = in_height
 			}
 		}
 
-		len = width * height;
-
-		assert!(len % 4 == 0, "area must be divisible by 4, {} given", len);
+		let area = match width.checked_mul(height) {
+			Some(area) if area > 0 => area,
+			_ => return Err(Error::InvalidDimensions(width, height))
+		};
 
 		let mut file = OpenOptions::new()
 			.create(true)
 			.truncate(true)
 			.read(true)
 			.write(true)
-			.open(&bin_path)
-			.unwrap();
+			.open(&bin_path)?;
 
-		file.set_len(len / 4 + (size_of::<u64>() * 2) as u64).unwrap();
+		file.set_len(packed_len(area) + size_of::<RawHeader>() as u64)?;
 
-		file.write_u64::<NativeEndian>(width).expect("error writing to file");
-		file.write_u64::<NativeEndian>(height).expect("error writing to file");
+		let mut header = RawHeader {
+			magic: *MAGIC,
+			version: FORMAT_VERSION.to_le(),
+			flags: 0,
+			width: width.to_le(),
+			height: height.to_le(),
+			generator: NO_GENERATOR.to_le(),
+			reserved: 0,
+			seed: [0; 4],
+			data_crc: 0,
+			header_crc: 0
+		};
+		header.header_crc = header_checksum(&header).to_le();
+
+		file.write_all(unsafe { header_bytes(&header) })?;
 
 		MMAPPackedGrid::from_file(file)
 	}
 
-	pub fn from_file(file: File) -> Self {
-		use std::os::unix::io::AsRawFd;
-		use std::mem::transmute;
+	pub fn from_file(file: File) -> Result<Self> {
+		let len = file.metadata()?.len();
+
+		if len < size_of::<LegacyRawHeader>() as u64 {
+			return Err(Error::InvalidFormat(
+				format!("file is too short to hold a maze, {} bytes", len)));
+		}
+
+		let mut mmapped = unsafe { MmapMut::map_mut(&file)? };
+
+		let data = mmapped.as_mut_ptr();
+		let has_magic = len >= size_of::<RawHeader>() as u64
+			&& unsafe { (*(data as *const RawHeader)).magic == *MAGIC };
+
+		let (header, header_len, width, height) = if has_magic {
+			let header = data as *mut RawHeader;
+			let raw = unsafe { &*header };
+
+			let version = u32::from_le(raw.version);
+			if version != FORMAT_VERSION {
+				return Err(Error::InvalidFormat(
+					format!("unsupported format version {}", version)));
+			}
+
+			if u32::from_le(raw.header_crc) != header_checksum(raw) {
+				return Err(Error::InvalidFormat(String::from("header checksum mismatch")));
+			}
+
+			(Some(header), size_of::<RawHeader>(), u64::from_le(raw.width), u64::from_le(raw.height))
+		} else {
+			let raw = unsafe { &*(data as *const LegacyRawHeader) };
+
+			(None, size_of::<LegacyRawHeader>(), raw.width, raw.height)
+		};
 
-		let len = file.metadata().unwrap().len() as usize;
-		
-		let mmapped = MemoryMap::new(len, &[
-			MapOption::MapReadable,
-			MapOption::MapWritable,
-			MapOption::MapFd(file.as_raw_fd()),
-			MapOption::MapNonStandardFlags(libc::MAP_SHARED)
-		]).unwrap();
+		if width == 0 || height == 0 {
+			return Err(Error::InvalidDimensions(width, height));
+		}
+
+		match cells_len(width, height) {
+			Some(cells_len) if cells_len.checked_add(header_len as u64) == Some(len) => {},
+			_ => return Err(Error::InvalidFormat(format!(
+				"not a maze file, {} bytes can't hold a {}x{} grid", len, width, height)))
+		}
+
+		let grid = MMAPPackedGrid {
+			header,
+			arr: unsafe { data.add(header_len) as *mut CArray<u8> },
 
-		MMAPPackedGrid {
-			raw: unsafe { transmute::<*mut u8, *mut RawPackedGrid>(mmapped.data()) },
+			width,
+			height,
+			dirty: AtomicBool::new(false),
 
 			_file: file,
-			_mmap: mmapped
+			mmap: mmapped
+		};
+
+		if grid.flags() & FLAG_CHECKSUM != 0 {
+			let expected = unsafe { u32::from_le((*header.unwrap()).data_crc) };
+
+			if grid.cells_checksum() != expected {
+				return Err(Error::InvalidFormat(
+					String::from("cell checksum mismatch, the file is corrupted")));
+			}
+		}
+
+		Ok(grid)
+	}
+
+	// 1 for files in the legacy layout
+	pub fn version(&self) -> u32 {
+		match self.header {
+			Some(_) => FORMAT_VERSION,
+			None => 1
+		}
+	}
+
+	pub fn flags(&self) -> u32 {
+		match self.header {
+			Some(header) => unsafe { u32::from_le((*header).flags) },
+			None => 0
+		}
+	}
+
+	pub fn generator(&self) -> Option<GeneratorType> {
+		match self.header {
+			Some(header) if self.flags() & FLAG_GENERATOR != 0 =>
+				GeneratorType::from_id(unsafe { u32::from_le((*header).generator) }),
+			_ => None
+		}
+	}
+
+	pub fn seed(&self) -> Option<Seed> {
+		match self.header {
+			Some(header) if self.flags() & FLAG_SEED != 0 => {
+				let mut words = unsafe { (*header).seed };
+
+				for word in words.iter_mut() {
+					*word = u32::from_le(*word);
+				}
+
+				Seed::from_words(&words)
+			},
+			_ => None
+		}
+	}
+
+	// records how the maze was generated, files in the legacy layout have no room for it
+	pub fn set_generator_info(&mut self, generator_type: GeneratorType, seed: Option<Seed>) {
+		if let Some(header) = self.header {
+			let raw = unsafe { &mut *header };
+			let mut flags = u32::from_le(raw.flags) | FLAG_GENERATOR;
+
+			raw.generator = (generator_type as u32).to_le();
+			raw.seed = [0; 4];
+
+			if let Some(seed) = seed {
+				for (word, &value) in raw.seed.iter_mut().zip(seed.to_words().iter()) {
+					*word = value.to_le();
+				}
+
+				flags |= FLAG_SEED;
+			} else {
+				flags &= !FLAG_SEED;
+			}
+
+			raw.flags = flags.to_le();
+			raw.header_crc = header_checksum(raw).to_le();
+		}
+	}
+
+	// brings the cell checksum up to date, done automatically when the grid is dropped
+	pub fn seal(&mut self) {
+		if let Some(header) = self.header {
+			let checksum = self.cells_checksum();
+			let raw = unsafe { &mut *header };
+
+			raw.data_crc = checksum.to_le();
+			raw.flags = (u32::from_le(raw.flags) | FLAG_CHECKSUM).to_le();
+			raw.header_crc = header_checksum(raw).to_le();
+		}
+
+		*self.dirty.get_mut() = false;
+	}
+
+	fn cells_checksum(&self) -> u32 {
+		use std::slice;
+
+		let len = cells_len(self.width, self.height).unwrap();
+		let mut crc = Crc32::new();
+
+		// checksum in pieces so that lengths beyond usize work on 32 bit targets as well
+		let mut offset = 0;
+		while offset < len {
+			let piece = ::std::cmp::min(len - offset, 1 << 20);
+
+			unsafe {
+				let start = (*self.arr).get_unchecked(offset as usize) as *const u8;
+				crc.update(slice::from_raw_parts(start, piece as usize));
+			}
+
+			offset += piece;
+		}
+
+		crc.finish()
+	}
+
+	// invalidates the stored checksum before the first change to the cells, past that it's a
+	// single load
+	#[inline(always)]
+	fn touch(&self) {
+		if !self.dirty.load(Ordering::Relaxed) {
+			self.mark_dirty();
+		}
+	}
+
+	// only the thread that sets the flag writes the header
+	#[cold]
+	fn mark_dirty(&self) {
+		if !self.dirty.swap(true, Ordering::AcqRel) {
+			if let Some(header) = self.header {
+				let raw = unsafe { &mut *header };
+
+				raw.flags = (u32::from_le(raw.flags) & !FLAG_CHECKSUM).to_le();
+				raw.header_crc = header_checksum(raw).to_le();
+			}
 		}
 	}
 
 	#[inline]
 	unsafe fn get_unpacked_unchecked(&self, x: u64, y: u64) -> &u8 {
-		(*self.raw).arr.get_unchecked(((y * self.width() + x) / 4) as usize)
+		(*self.arr).get_unchecked(((y * self.width() + x) / 4) as usize)
 	}
 
 	#[inline]
 	unsafe fn get_unpacked_unchecked_mut(&mut self, x: u64, y: u64) -> &mut u8 {
-		(*self.raw).arr.get_unchecked_mut(((y * self.width() + x) / 4) as usize)
+		let width = self.width();
+		(*self.arr).get_unchecked_mut(((y * width + x) / 4) as usize)
+	}
+
+}
+
+unsafe fn header_bytes(header: &RawHeader) -> &[u8] {
+	use std::slice;
+
+	slice::from_raw_parts(header as *const RawHeader as *const u8, size_of::<RawHeader>())
+}
+
+// the checksum covers everything up to the checksum field itself
+fn header_checksum(header: &RawHeader) -> u32 {
+	let mut crc = Crc32::new();
+	crc.update(unsafe { &header_bytes(header)[..size_of::<RawHeader>() - size_of::<u32>()] });
+	crc.finish()
+}
+
+impl Drop for MMAPPackedGrid {
+	fn drop(&mut self) {
+		if *self.dirty.get_mut() {
+			self.seal();
+		}
+	}
+}
+
+impl PackedGrid for MMAPPackedGrid {
+	fn new(options: &[PackedOption]) -> Result<Self> {
+		MMAPPackedGrid::new(options)
 	}
 
+	fn record_generator(&mut self, generator_type: GeneratorType, seed: Option<Seed>) {
+		self.set_generator_info(generator_type, seed);
+	}
 }
 
 impl Grid for MMAPPackedGrid {
 	#[inline(always)]
 	fn width(&self) -> u64 {
-		unsafe { (*self.raw).width }
+		self.width
 	}
 
 	#[inline(always)]
 	fn height(&self) -> u64 {
-		unsafe { (*self.raw).height }
+		self.height
 	}
 
 	fn fill(&mut self, fill: u8) {
-		for i in 0..(self.width() * self.height() / 4) {
-			unsafe { *(*self.raw).arr.get_unchecked_mut(i as usize) = fill; }
+		self.touch();
+
+		let len = cells_len(self.width, self.height).unwrap();
+
+		for i in 0..len {
+			unsafe { *(*self.arr).get_unchecked_mut(i as usize) = fill; }
+		}
+
+		if len > 0 {
+			unsafe {
+				*(*self.arr).get_unchecked_mut(len as usize - 1) &= trailing_mask(self.width * self.height);
+			}
 		}
 	}
 
@@ -130,6 +409,7 @@ impl Grid for MMAPPackedGrid {
 	{
 		let nth = ((y * self.width() + x) % 4) as u8;
 
+		self.touch();
 		*self.get_unpacked_unchecked_mut(x, y) &= !(0b11 << (nth * 2));
 		*self.get_unpacked_unchecked_mut(x, y) |= prepare_bits(value, nth);
 	}
@@ -137,6 +417,7 @@ impl Grid for MMAPPackedGrid {
 	#[inline(always)]
 	unsafe fn or_set_unchecked(&mut self, x: u64, y: u64, value: u8)
 	{
+		self.touch();
 		*self.get_unpacked_unchecked_mut(x, y) |=
 			prepare_bits(value, ((y * self.width() + x) & 0b11) as u8);
 	}
@@ -144,7 +425,13 @@ impl Grid for MMAPPackedGrid {
 	#[inline(always)]
 	unsafe fn unset_provided_unchecked(&mut self, x: u64, y: u64, value: u8)
 	{
+		self.touch();
 		*self.get_unpacked_unchecked_mut(x, y) &=
 			!prepare_bits(value, ((y * self.width() + x) & 0b11) as u8)
 	}
+
+	fn flush(&self) -> Result<()> {
+		self.mmap.flush()?;
+		Ok(())
+	}
 }
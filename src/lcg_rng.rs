@@ -10,6 +10,11 @@ impl LCGRng {
 	pub fn new_unseeded() -> LCGRng {
 		LCGRng(w(0))
 	}
+
+	// the generator continues from here when seeded with it
+	pub fn state(&self) -> u64 {
+		(self.0).0
+	}
 }
 
 impl Rng for LCGRng {
@@ -1,13 +1,21 @@
-#![feature(scoped)]
-#![feature(result_expect)]
-#![feature(cstr_to_str)]
-#![feature(box_raw)]
-
 pub mod generator;
 pub mod grid;
 pub mod solver;
 pub mod lcg_rng;
+pub mod rdrand_rng;
 pub mod extern_c;
+pub mod validate;
+pub mod analysis;
+pub mod render;
+pub mod error;
+
+mod bitset;
+mod crc32;
+
+pub use error::{Error, Result};
+pub use grid::in_memory_packed_grid::InMemoryPackedGrid;
+pub use grid::mmap_packed_grid::MMAPPackedGrid;
+pub use utils::Direction;
 
 mod utils {
 	use std::convert::From;
@@ -75,9 +83,24 @@ mod utils {
 	pub fn prepare_bits(value: u8, nth: u8) -> u8 {
 		(value & 0b11) << (nth * 2)
 	}
+
+	// the number of bytes holding `area` packed cells, the last one may be partially used
+	#[inline(always)]
+	pub fn packed_len(area: u64) -> u64 {
+		area.div_ceil(4)
+	}
+
+	// the bits of the last byte that belong to cells, the rest is kept zero
+	#[inline(always)]
+	pub fn trailing_mask(area: u64) -> u8 {
+		match area % 4 {
+			0 => 0xFF,
+			n => (1 << (n * 2)) - 1
+		}
+	}
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 #[repr(C)]
 pub enum GeneratorType {
 	Sidewinder,
@@ -86,21 +109,94 @@ pub enum GeneratorType {
 	StackBacktrack,
 	RecursiveDivision,
 	StackDivision,
-	EllersAlgorithm
+	EllersAlgorithm,
+	ParallelSidewinder,
+	Prims,
+	Kruskal,
+	Wilson,
+	AldousBroder,
+	// starts out as Aldous-Broder and finishes with Wilson's
+	AldousBroderWilson,
+	GrowingTree,
+	HuntAndKill,
+	BinaryTree
+}
+
+impl GeneratorType {
+	pub fn from_id(id: u32) -> Option<GeneratorType> {
+		use self::GeneratorType::*;
+
+		let all = [
+			Sidewinder,
+			NaiveSidewinder,
+			RecursiveBacktrack,
+			StackBacktrack,
+			RecursiveDivision,
+			StackDivision,
+			EllersAlgorithm,
+			ParallelSidewinder,
+			Prims,
+			Kruskal,
+			Wilson,
+			AldousBroder,
+			AldousBroderWilson,
+			GrowingTree,
+			HuntAndKill,
+			BinaryTree
+		];
+
+		all.iter().cloned().find(|&generator_type| generator_type as u32 == id)
+	}
 }
 
+#[derive(Clone)]
 pub enum PackedOption {
 	MMAPFilePath(String),
 	Width(u64),
 	Height(u64)
 }
 
+#[derive(Clone, Copy, Debug)]
+pub enum SolverOption {
+	Start(u64, u64),
+	Goal(u64, u64)
+}
+
+#[derive(Clone)]
 pub enum GeneratorOption {
-	Seed(&'static [u32])
+	// seeds the generator, see generator::Seed for the encoding older versions used
+	Seed(generator::Seed),
+	ThreadCount(u64),
+	// keeps the union-find of the Kruskal generator in a memory mapped file at the path
+	UnionFindFilePath(String),
+	// keeps the frontier of the Prim's generator in a memory mapped file at the path
+	FrontierFilePath(String),
+	// how the growing tree generator picks the cell to carve from, newest if not given
+	CellSelection(generator::CellSelection),
+	// after generating, removes this fraction (0 to 1) of the dead ends, rounded, by adding loops
+	Braid(f64),
+	// the division generators leave chambers narrower or shorter than this open as rooms
+	RoomSize(u64),
+	// how much the division generators prefer walls across the longer side, 0.35 by default
+	OrientationBias(f64),
+	// the number of openings in every wall of the division generators, 1 by default
+	WallOpenings(u64),
+	// the chance (0 to 1) of the division generators leaving a chamber undivided
+	UndividedChance(f64),
+	// reports the progress of the generation and lets it be cancelled
+	Progress(generator::Progress),
+	// saves the state of the generation to the file at the path every given number of cells
+	// and resumes from it if it exists, supported by the stack backtrack and Eller generators
+	Checkpoint(String, u64)
 }
 
 pub trait Generator {
 	fn generate(&mut self);
+
+	// an error the generation ran into, only generators that write files can fail
+	fn take_error(&mut self) -> Option<Error> {
+		None
+	}
 }
 
 pub trait Grid: Send + Sync {
@@ -110,14 +206,31 @@ pub trait Grid: Send + Sync {
 
 	fn fill(&mut self, fill: u8);
 
+	/// # Safety
+	///
+	/// `x` and `y` must be within the width and the height of the grid.
 	unsafe fn get_unchecked(&self, x: u64, y: u64) -> u8;
 
+	/// # Safety
+	///
+	/// `x` and `y` must be within the width and the height of the grid.
 	unsafe fn set_unchecked(&mut self, x: u64, y: u64, value: u8);
 
+	/// # Safety
+	///
+	/// `x` and `y` must be within the width and the height of the grid.
 	unsafe fn or_set_unchecked(&mut self, x: u64, y: u64, value: u8);
 
+	/// # Safety
+	///
+	/// `x` and `y` must be within the width and the height of the grid.
 	unsafe fn unset_provided_unchecked(&mut self, x: u64, y: u64, value: u8);
 
+	// makes the changes so far durable, grids that aren't backed by a file have nothing to do
+	fn flush(&self) -> Result<()> {
+		Ok(())
+	}
+
 	fn get(&self, x: u64, y: u64) -> u8 {
 		assert!(x < self.width() && y < self.height(),
 			"{} < {} && {} < {}", x, self.width(), y, self.height());
@@ -163,36 +276,80 @@ pub trait Grid: Send + Sync {
 
 		let mut buf = String::from(" ");
 
-		buf = buf + &String::from_utf8(vec![b'_'; self.width() as usize * 2 - 1]).unwrap();
-		buf = buf + "\n";
+		buf += &String::from_utf8(vec![b'_'; self.width() as usize * 2 - 1]).unwrap();
+		buf += "\n";
 
 		for y in 0..self.height() {
-			buf = buf + "|";
+			buf += "|";
 			
 			for x in 0..self.width() {
-				buf = buf + if self.get(x, y) & S as u8 == 0 { "_" } else { " " };
+				buf += if self.get(x, y) & S as u8 == 0 { "_" } else { " " };
 
-				buf = buf + if self.get(x, y) & E as u8 == 0 { "|" } else { "." };
+				buf += if self.get(x, y) & E as u8 == 0 { "|" } else { "." };
 			}
 
-			buf = buf + "\n";
+			buf += "\n";
 		}
 
 		buf
 	}
+
+	fn to_svg(&self) -> String where Self: Sized {
+		use self::render::{svg, RenderOptions};
+
+		let mut buf = Vec::new();
+
+		svg::write(self, None, None, &RenderOptions::default(), &mut buf)
+			.expect("writing to a Vec can't fail");
+
+		String::from_utf8(buf).unwrap()
+	}
+}
+
+pub trait PackedGrid: Grid + Sized {
+	fn new(options: &[PackedOption]) -> Result<Self>;
+
+	// grids that are persisted may keep track of how their maze was generated
+	fn record_generator(&mut self, _generator_type: GeneratorType, _seed: Option<generator::Seed>) {}
 }
 
 pub type SolverSolution = Vec<utils::Direction>;
 
 pub trait Solver {
-	fn solve(mut self) -> Option<SolverSolution>;
+	fn solve(self) -> Option<SolverSolution>;
 }
 
-pub fn generate<G: Grid>(grid: &mut G, generator_type: GeneratorType, options: &[GeneratorOption]) {
+pub fn generate<G: Grid>(grid: &mut G, generator_type: GeneratorType, options: &[GeneratorOption])
+	-> Result<()> {
 	use self::GeneratorType::*;
 	use self::generator::*;
 
-	let mut generator: Box<Generator + Sized> = match generator_type {
+	if grid.width() == 0 || grid.height() == 0 {
+		return Err(Error::InvalidDimensions(grid.width(), grid.height()));
+	}
+
+	for o in options {
+		match *o {
+			GeneratorOption::Braid(fraction) if !(0.0..=1.0).contains(&fraction) =>
+				return Err(Error::InvalidArgument(format!("braid fraction {} is not within 0 and 1", fraction))),
+			GeneratorOption::UndividedChance(chance) if !(0.0..=1.0).contains(&chance) =>
+				return Err(Error::InvalidArgument(format!("undivided chance {} is not within 0 and 1", chance))),
+			GeneratorOption::WallOpenings(0) =>
+				return Err(Error::InvalidArgument(String::from("walls need at least one opening"))),
+			GeneratorOption::Checkpoint(..) => match generator_type {
+				StackBacktrack | EllersAlgorithm => {},
+				_ => return Err(Error::InvalidArgument(
+					format!("the {:?} generator can't be resumed from a checkpoint", generator_type)))
+			},
+
+			_ => {}
+		}
+	}
+
+	let progress = Progress::from_options(options);
+	progress.start(grid.width() * grid.height());
+
+	let mut generator: Box<dyn Generator + '_> = match generator_type {
 		Sidewinder => 
 			Box::new(SidewinderGenerator::new(grid, options)),
 		NaiveSidewinder => 
@@ -205,9 +362,201 @@ pub fn generate<G: Grid>(grid: &mut G, generator_type: GeneratorType, options: &
 			Box::new(RecursiveDivisionGenerator::new(grid, options)),
 		StackDivision => 
 			Box::new(StackDivisionGenerator::new(grid, options)),
-
-		_ => panic!("\"{:?}\" generator algorithm not yet implemented", generator_type)
+		EllersAlgorithm => 
+			Box::new(EllersGenerator::new(grid, options)),
+		ParallelSidewinder => 
+			Box::new(ParallelSidewinderGenerator::new(grid, options)),
+		Prims => 
+			Box::new(PrimsGenerator::new(grid, options)?),
+		Kruskal => 
+			Box::new(KruskalGenerator::new(grid, options)?),
+		Wilson => 
+			Box::new(WilsonsGenerator::new(grid, options)),
+		AldousBroder => 
+			Box::new(AldousBroderGenerator::new(grid, options)),
+		AldousBroderWilson => 
+			Box::new(WilsonsGenerator::hybrid(grid, options)),
+		GrowingTree => 
+			Box::new(GrowingTreeGenerator::new(grid, options)),
+		HuntAndKill => 
+			Box::new(HuntAndKillGenerator::new(grid, options)),
+		BinaryTree => 
+			Box::new(BinaryTreeGenerator::new(grid, options))
 	};
 
 	generator.generate();
+	let error = generator.take_error();
+	drop(generator);
+
+	// the grid is left as it is, so the generation can be resumed from its checkpoint
+	if let Some(e) = error {
+		return Err(e);
+	}
+
+	let resumable = options.iter().any(|o| matches!(*o, GeneratorOption::Checkpoint(..)));
+
+	// a cancelled generation leaves every wall in place rather than a partial maze, unless
+	// it is to be resumed
+	if progress.is_cancelled() {
+		if !resumable {
+			grid.fill(0);
+		}
+
+		return Err(Error::Cancelled);
+	}
+
+	for o in options {
+		if let GeneratorOption::Braid(fraction) = *o {
+			braid(grid, fraction, options);
+		}
+	}
+
+	progress.finish();
+
+	Ok(())
+}
+
+pub struct Maze<G: Grid> {
+	grid: G,
+	solution: Option<SolverSolution>
+}
+
+impl<G: Grid> Maze<G> {
+	pub fn grid(&self) -> &G {
+		&self.grid
+	}
+
+	pub fn grid_mut(&mut self) -> &mut G {
+		&mut self.grid
+	}
+
+	pub fn into_grid(self) -> G {
+		self.grid
+	}
+
+	pub fn solution(&self) -> Option<&SolverSolution> {
+		self.solution.as_ref()
+	}
+}
+
+pub struct MazeBuilder {
+	width: u64,
+	height: u64,
+	file_path: Option<String>,
+	generator_type: GeneratorType,
+	seed: Option<generator::Seed>,
+	braid: Option<f64>,
+	solve: bool,
+	solver_options: Vec<SolverOption>
+}
+
+impl Default for MazeBuilder {
+	fn default() -> Self {
+		MazeBuilder::new()
+	}
+}
+
+impl MazeBuilder {
+	pub fn new() -> Self {
+		MazeBuilder {
+			width: 32,
+			height: 32,
+			file_path: None,
+			generator_type: GeneratorType::StackBacktrack,
+			seed: None,
+			braid: None,
+			solve: false,
+			solver_options: Vec::new()
+		}
+	}
+
+	pub fn width(mut self, width: u64) -> Self {
+		self.width = width;
+		self
+	}
+
+	pub fn height(mut self, height: u64) -> Self {
+		self.height = height;
+		self
+	}
+
+	pub fn file_path(mut self, file_path: String) -> Self {
+		self.file_path = Some(file_path);
+		self
+	}
+
+	pub fn generate_using(mut self, generator_type: GeneratorType) -> Self {
+		self.generator_type = generator_type;
+		self
+	}
+
+	pub fn seed<S: Into<generator::Seed>>(mut self, seed: S) -> Self {
+		self.seed = Some(seed.into());
+		self
+	}
+
+	pub fn braid(mut self, fraction: f64) -> Self {
+		self.braid = Some(fraction);
+		self
+	}
+
+	pub fn solve(mut self, solve: bool) -> Self {
+		self.solve = solve;
+		self
+	}
+
+	pub fn solve_from(mut self, x: u64, y: u64) -> Self {
+		self.solver_options.push(SolverOption::Start(x, y));
+		self
+	}
+
+	pub fn solve_to(mut self, x: u64, y: u64) -> Self {
+		self.solver_options.push(SolverOption::Goal(x, y));
+		self
+	}
+
+	// constructs the grid, generates the maze in it and, if asked to, finds the shortest path
+	// from the start to the nearest goal.
+	//
+	// `options` is passed on to the grid, the dimensions and file path set on the builder
+	// take precedence over the ones given there.
+	pub fn build<G: PackedGrid>(self, options: &[PackedOption]) -> Result<Maze<G>> {
+		use self::PackedOption::*;
+		use self::solver::bf_solver::BFSolver;
+
+		let mut packed_options: Vec<PackedOption> = options.to_vec();
+
+		if let Some(file_path) = self.file_path {
+			packed_options.push(MMAPFilePath(file_path));
+		}
+
+		packed_options.push(Width(self.width));
+		packed_options.push(Height(self.height));
+
+		let mut generator_options = Vec::new();
+
+		if let Some(seed) = self.seed {
+			generator_options.push(GeneratorOption::Seed(seed));
+		}
+
+		if let Some(fraction) = self.braid {
+			generator_options.push(GeneratorOption::Braid(fraction));
+		}
+
+		let mut grid = G::new(&packed_options)?;
+
+		generate(&mut grid, self.generator_type, &generator_options)?;
+		grid.record_generator(self.generator_type, self.seed);
+
+		let solution = if self.solve {
+			BFSolver::new(&grid, &self.solver_options)?.solve()
+		} else {
+			None
+		};
+
+		Ok(Maze {
+			grid,
+			solution
+		})
+	}
 }
@@ -1,59 +1,150 @@
-#![feature(plugin)]
-#![plugin(docopt_macros)]
-
 extern crate docopt;
-extern crate rustc_serialize; //needed by docopt_macros
-extern crate regex;
+extern crate serde;
 
 extern crate maze;
 
 use std::env;
+use std::process;
+use std::result;
+use std::fs::OpenOptions;
 use std::str::FromStr;
+use docopt::Docopt;
+use serde::Deserialize;
 use maze::*;
-use maze::grid::mmap_packed_grid::MMAPPackedGrid;
-use maze::solver::stack_df_solver::StackDFSolver;
+use maze::solver::bf_solver::BFSolver;
 
-docopt!(Args derive Debug, "
+const USAGE: &str = "
 Usage:
-  maze generate <width> <height> [<location>] [--print] [--solve]
+  maze generate <width> <height> [<location>] [--seed=<seed>] [--print] [--solve] [--start=<x,y>] [--goal=<x,y>...]
+  maze verify <location>
+  maze stats <location> [--json]
   maze (--help | --version)
 
 Options:
   --help  Show this message.
   --version   Show version.
-");
+  --seed=<seed>  Seed of the generator, a number or any other text [default: 0].
+  --solve  Find the shortest path from the start to the nearest goal.
+  --start=<x,y>  Cell to start solving from [default: 0,0].
+  --goal=<x,y>  Cell to solve towards, can be given several times, defaults to the bottom right corner.
+  --json  Print the statistics as JSON.
+";
 
-fn main() {
-	use maze::PackedOption::*;
+#[derive(Debug, Deserialize)]
+struct Args {
+	cmd_generate: bool,
+	cmd_verify: bool,
+	cmd_stats: bool,
+	arg_width: String,
+	arg_height: String,
+	arg_location: String,
+	flag_seed: String,
+	flag_print: bool,
+	flag_solve: bool,
+	flag_start: String,
+	flag_goal: Vec<String>,
+	flag_json: bool,
+	flag_version: bool
+}
 
-	let args: Args = Args::docopt().decode().unwrap_or_else(|e| e.exit());
+fn main() {
+	let args: Args = Docopt::new(USAGE)
+		.and_then(|d| d.deserialize())
+		.unwrap_or_else(|e| e.exit());
 	
 	if args.cmd_generate {
 		let width: u64 = FromStr::from_str(&args.arg_width).unwrap_or(32);
 		let height: u64 = FromStr::from_str(&args.arg_height).unwrap_or(32);
 		let path = FromStr::from_str(&args.arg_location).unwrap_or(String::from(env::current_dir().unwrap().to_str().unwrap()));
 
-		let mut _maze = MMAPPackedGrid::new(&[
-			MMAPFilePath(format!("{}/maze_{}x{}.bin", path, width, height)),
-			Width(width),
-			Height(height)
-		]);
+		let mut builder = MazeBuilder::new()
+			.width(width)
+			.height(height)
+			.file_path(format!("{}/maze_{}x{}.bin", path, width, height))
+			.generate_using(GeneratorType::RecursiveBacktrack)
+			.seed(parse_seed(&args.flag_seed))
+			.solve(args.flag_solve);
 
-		static SEED: [u32; 2] = [0x00, 0x00];
+		let (start_x, start_y) = parse_cell(&args.flag_start).unwrap_or_else(|e| usage_error(&e));
+		builder = builder.solve_from(start_x, start_y);
 
-		generate(&mut _maze, GeneratorType::RecursiveBacktrack, &[GeneratorOption::Seed(&SEED)]);
+		for goal in args.flag_goal.iter() {
+			let (goal_x, goal_y) = parse_cell(goal).unwrap_or_else(|e| usage_error(&e));
+			builder = builder.solve_to(goal_x, goal_y);
+		}
+
+		let maze = builder.build::<MMAPPackedGrid>(&[]).unwrap_or_else(|e| {
+			eprintln!("Can't generate the maze: {}", e);
+			process::exit(2);
+		});
 
 		if args.flag_print {
-			println!("{}", _maze.to_string());
+			println!("{}", maze.grid().to_string());
 		}
 
 		if args.flag_solve {
-			let solver = StackDFSolver::new(&_maze);
-			let directions = solver.solve();
+			println!("Solution: {:?}", maze.solution());
+		}
+	} else if args.cmd_verify {
+		let grid = open_grid(&args.arg_location);
+		let report = validate::validate(&grid);
 
-			println!("Solution: {:?}", directions);
+		println!("{}", report);
+
+		if !report.is_perfect() {
+			process::exit(1);
+		}
+	} else if args.cmd_stats {
+		let grid = open_grid(&args.arg_location);
+		let solution = BFSolver::new(&grid, &[]).ok().and_then(|solver| solver.solve());
+		let stats = analysis::analyze(&grid, solution.as_ref());
+
+		if args.flag_json {
+			println!("{}", stats.to_json());
+		} else {
+			println!("{}", stats);
 		}
 	} else if args.flag_version {
 		println!("{}", env!("CARGO_PKG_VERSION"));
 	}
 }
+
+// numbers are taken as they are, anything else is hashed
+fn parse_seed(seed: &str) -> generator::Seed {
+	match u128::from_str(seed) {
+		Ok(value) => generator::Seed::from(value),
+		Err(_) => generator::Seed::from(seed)
+	}
+}
+
+fn open_grid(path: &str) -> MMAPPackedGrid {
+	let opened = OpenOptions::new().read(true).write(true).open(path)
+		.map_err(Error::from)
+		.and_then(MMAPPackedGrid::from_file);
+
+	match opened {
+		Ok(grid) => grid,
+		Err(e) => {
+			eprintln!("Can't open \"{}\": {}", path, e);
+			process::exit(2);
+		}
+	}
+}
+
+fn parse_cell(cell: &str) -> Result<(u64, u64)> {
+	let coordinates: Vec<u64> = cell.split(',')
+		.map(|c| FromStr::from_str(c.trim()))
+		.collect::<result::Result<_, _>>()
+		.map_err(|_| Error::InvalidArgument(format!("invalid cell \"{}\"", cell)))?;
+
+	if coordinates.len() != 2 {
+		return Err(Error::InvalidArgument(format!("cells are given as x,y but \"{}\" given", cell)));
+	}
+
+	Ok((coordinates[0], coordinates[1]))
+}
+
+fn usage_error(error: &Error) -> ! {
+	eprintln!("{}\n{}", error, USAGE.trim());
+	process::exit(1);
+}
@@ -8,26 +8,47 @@ pub struct RDRandRng;
 unsafe impl Send for RDRandRng {}
 unsafe impl Sync for RDRandRng {}
 
-#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-#[inline]
+// RDRAND may fail when the entropy source is exhausted, in which case it has to be retried
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "rdrand")]
 unsafe fn rdrand_u32() -> u32 {
-	let out: u32;
+	use std::arch::x86_64::_rdrand32_step;
 
-	asm!("rdrand $0;" : "=r"(out) ::: "volatile");
+	let mut out = 0;
+	while _rdrand32_step(&mut out) == 0 {}
 
 	out
 }
 
-#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
-#[inline]
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "rdrand")]
 unsafe fn rdrand_u64() -> u64 {
-	let out: u64;
+	use std::arch::x86_64::_rdrand64_step;
+
+	let mut out = 0;
+	while _rdrand64_step(&mut out) == 0 {}
+
+	out
+}
+
+#[cfg(target_arch = "x86")]
+#[target_feature(enable = "rdrand")]
+unsafe fn rdrand_u32() -> u32 {
+	use std::arch::x86::_rdrand32_step;
 
-	asm!("rdrand $0;" : "=r"(out) ::: "volatile");
+	let mut out = 0;
+	while _rdrand32_step(&mut out) == 0 {}
 
 	out
 }
 
+#[cfg(target_arch = "x86")]
+#[target_feature(enable = "rdrand")]
+unsafe fn rdrand_u64() -> u64 {
+	((rdrand_u32() as u64) << 32) | rdrand_u32() as u64
+}
+
 
 
 #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
@@ -41,7 +62,21 @@ unsafe fn rdrand_u64() -> u64 {
 }
 
 impl RDRandRng {
+	pub fn is_supported() -> bool {
+		#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+		{
+			is_x86_feature_detected!("rdrand")
+		}
+
+		#[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+		{
+			false
+		}
+	}
+
 	pub fn new_unseeded() -> RDRandRng {
+		assert!(RDRandRng::is_supported(), "RDRAND not supported by this CPU");
+
 		RDRandRng
 	}
 }
@@ -49,6 +84,7 @@ impl RDRandRng {
 impl Rng for RDRandRng {
 	#[inline]
 	fn next_u32(&mut self) -> u32 {
+		// NOTE: only constructed once RDRAND support has been checked
 		unsafe { rdrand_u32() }
 	}
 
@@ -60,6 +96,6 @@ impl Rng for RDRandRng {
 
 impl Rand for RDRandRng {
 	fn rand<R: Rng>(_: &mut R) -> RDRandRng {
-		RDRandRng
+		RDRandRng::new_unseeded()
 	}
 }
@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+use ::SolverSolution;
+
+pub mod png;
+pub mod svg;
+
+#[derive(Clone, Debug)]
+pub struct RenderOptions {
+	// size of a cell in pixels, including one of its walls
+	pub cell_size: u32,
+	pub wall_thickness: u32,
+	pub wall_color: [u8; 3],
+	pub background_color: [u8; 3],
+	pub path_color: [u8; 3]
+}
+
+impl Default for RenderOptions {
+	fn default() -> Self {
+		RenderOptions {
+			cell_size: 10,
+			wall_thickness: 2,
+			wall_color: [0x00, 0x00, 0x00],
+			background_color: [0xFF, 0xFF, 0xFF],
+			path_color: [0xE0, 0x20, 0x20]
+		}
+	}
+}
+
+// a solution to draw on top of the maze, followed from its start cell
+pub struct Path<'a> {
+	pub start: (u64, u64),
+	pub directions: &'a SolverSolution
+}
+
+impl<'a> Path<'a> {
+	// the cells on the path, each with the directions the path leaves it in
+	pub fn cells(&self) -> HashMap<(u64, u64), u8> {
+		use ::utils::Direction::*;
+
+		let mut cells = HashMap::new();
+		let (mut x, mut y) = self.start;
+
+		cells.insert((x, y), 0);
+
+		for &dir in self.directions.iter() {
+			*cells.entry((x, y)).or_insert(0) |= dir as u8;
+
+			match dir {
+				S => y += 1,
+				E => x += 1,
+				N => y -= 1,
+				W => x -= 1
+			}
+
+			*cells.entry((x, y)).or_insert(0) |= dir.opposite() as u8;
+		}
+
+		cells
+	}
+}
@@ -0,0 +1,281 @@
+extern crate byteorder;
+
+use std::io::{self, Write};
+use ::Grid;
+use ::crc32::Crc32;
+use ::utils::Direction::{S, E};
+use self::byteorder::{BigEndian, WriteBytesExt};
+use super::{RenderOptions, Path};
+
+const BACKGROUND: u8 = 0;
+const WALL: u8 = 1;
+const PATH: u8 = 2;
+
+// the image data is written out in IDAT chunks of about this size
+const CHUNK_SIZE: usize = 1 << 16;
+
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const MAX_DISTANCE: usize = 32768;
+
+const LENGTH_BASES: [u16; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51,
+	59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+const LENGTH_EXTRA_BITS: [u32; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4,
+	4, 4, 4, 5, 5, 5, 5, 0];
+const DISTANCE_BASES: [u16; 30] = [1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257,
+	385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577];
+const DISTANCE_EXTRA_BITS: [u32; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8,
+	9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+
+enum Position {
+	Wall(u64),
+	Cell(u64)
+}
+
+fn locate(pixel: u64, options: &RenderOptions) -> Position {
+	let cell_size = options.cell_size as u64;
+
+	if pixel % cell_size < options.wall_thickness as u64 {
+		Position::Wall(pixel / cell_size)
+	} else {
+		Position::Cell(pixel / cell_size)
+	}
+}
+
+// renders the grid as a 2 bit palette PNG
+//
+// the image is produced and compressed one pixel row at a time, so no more than a couple of
+// rows and an IDAT chunk are ever held in memory
+pub fn write<G: Grid, W: Write>(grid: &G, path: Option<&Path>, options: &RenderOptions,
+	out: &mut W) -> io::Result<()> {
+	use self::Position::*;
+
+	if options.cell_size <= options.wall_thickness {
+		return Err(io::Error::new(io::ErrorKind::InvalidInput,
+			"cell size must be larger than the wall thickness"));
+	}
+
+	let (width, height) = (grid.width(), grid.height());
+	let cell_size = options.cell_size as u64;
+	let image_width = width * cell_size + options.wall_thickness as u64;
+	let image_height = height * cell_size + options.wall_thickness as u64;
+
+	if image_width > 0x7FFFFFFF || image_height > 0x7FFFFFFF {
+		return Err(io::Error::new(io::ErrorKind::InvalidInput, "image would be too large for PNG"));
+	}
+
+	let path_cells = path.map(|p| p.cells()).unwrap_or_default();
+	let path_bits = |x: u64, y: u64| path_cells.get(&(x, y)).cloned();
+
+	out.write_all(b"\x89PNG\r\n\x1a\n")?;
+
+	let mut header = Vec::with_capacity(13);
+	header.write_u32::<BigEndian>(image_width as u32)?;
+	header.write_u32::<BigEndian>(image_height as u32)?;
+	// bit depth 2, indexed colour, deflate, no filtering, no interlacing
+	header.write_all(&[2, 3, 0, 0, 0])?;
+	write_chunk(out, b"IHDR", &header)?;
+
+	let mut palette = Vec::with_capacity(9);
+	palette.write_all(&options.background_color)?;
+	palette.write_all(&options.wall_color)?;
+	palette.write_all(&options.path_color)?;
+	write_chunk(out, b"PLTE", &palette)?;
+
+	let mut image_data = ZlibWriter::new();
+	let mut scanline = vec![0u8; 1 + image_width.div_ceil(4) as usize];
+
+	for py in 0..image_height {
+		let row = locate(py, options);
+
+		for byte in scanline.iter_mut() {
+			*byte = 0;
+		}
+
+		for px in 0..image_width {
+			let colour = match (locate(px, options), &row) {
+				(Wall(_), &Wall(_)) => WALL,
+				(Wall(x), &Cell(y)) => {
+					if x > 0 && x < width && grid.get(x - 1, y) & E as u8 != 0 {
+						if path_bits(x - 1, y).is_some_and(|bits| bits & E as u8 != 0) {
+							PATH
+						} else {
+							BACKGROUND
+						}
+					} else {
+						WALL
+					}
+				},
+				(Cell(x), &Wall(y)) => {
+					if y > 0 && y < height && grid.get(x, y - 1) & S as u8 != 0 {
+						if path_bits(x, y - 1).is_some_and(|bits| bits & S as u8 != 0) {
+							PATH
+						} else {
+							BACKGROUND
+						}
+					} else {
+						WALL
+					}
+				},
+				(Cell(x), &Cell(y)) => {
+					if path_bits(x, y).is_some() { PATH } else { BACKGROUND }
+				}
+			};
+
+			// the first byte of every scanline is its filter type
+			scanline[1 + (px / 4) as usize] |= colour << (6 - (px % 4) * 2);
+		}
+
+		image_data.write(out, &scanline)?;
+	}
+
+	image_data.finish(out)?;
+
+	write_chunk(out, b"IEND", &[])
+}
+
+fn write_chunk<W: Write>(out: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+	let mut crc = Crc32::new();
+	crc.update(kind);
+	crc.update(data);
+
+	out.write_u32::<BigEndian>(data.len() as u32)?;
+	out.write_all(kind)?;
+	out.write_all(data)?;
+	out.write_u32::<BigEndian>(crc.finish())
+}
+
+// wraps the image data into a zlib stream of a single deflate block with the fixed Huffman
+// codes, written out as IDAT chunks of about CHUNK_SIZE bytes
+//
+// mazes are runs of the same colour and rows repeating the row above them, so every scanline
+// is matched against itself one byte back and against the previous scanline, which keeps just
+// the two scanlines in memory
+struct ZlibWriter {
+	previous: Vec<u8>,
+	last_byte: Option<u8>,
+	bits: u64,
+	bit_count: u32,
+	chunk: Vec<u8>,
+	adler: (u32, u32)
+}
+
+impl ZlibWriter {
+	fn new() -> Self {
+		let mut writer = ZlibWriter {
+			previous: Vec::new(),
+			last_byte: None,
+			bits: 0,
+			bit_count: 0,
+			// deflate with a 32K window, no preset dictionary, fastest compression
+			chunk: vec![0x78, 0x01],
+			adler: (1, 0)
+		};
+
+		// the only block, final and compressed with the fixed codes
+		writer.write_bits(0b011, 3);
+		writer
+	}
+
+	// the scanlines have to be of the same length
+	fn write<W: Write>(&mut self, out: &mut W, scanline: &[u8]) -> io::Result<()> {
+		for &byte in scanline {
+			self.adler.0 = (self.adler.0 + byte as u32) % 65521;
+			self.adler.1 = (self.adler.1 + self.adler.0) % 65521;
+		}
+
+		let row_distance = scanline.len();
+		let above = !self.previous.is_empty() && row_distance <= MAX_DISTANCE;
+		let mut i = 0;
+
+		while i < scanline.len() {
+			let limit = ::std::cmp::min(MAX_MATCH, scanline.len() - i);
+
+			let run = match if i == 0 { self.last_byte } else { Some(scanline[i - 1]) } {
+				Some(byte) => scanline[i..i + limit].iter().take_while(|&&b| b == byte).count(),
+				None => 0
+			};
+
+			let repeated = if above {
+				scanline[i..i + limit].iter().zip(self.previous[i..].iter())
+					.take_while(|&(a, b)| a == b).count()
+			} else {
+				0
+			};
+
+			if run.max(repeated) >= MIN_MATCH {
+				let (len, distance) = if repeated >= run { (repeated, row_distance) } else { (run, 1) };
+
+				self.write_match(len, distance);
+				i += len;
+			} else {
+				self.write_symbol(scanline[i] as u16);
+				i += 1;
+			}
+
+			if self.chunk.len() >= CHUNK_SIZE {
+				write_chunk(out, b"IDAT", &self.chunk)?;
+				self.chunk.clear();
+			}
+		}
+
+		self.last_byte = scanline.last().cloned();
+		self.previous.clear();
+		self.previous.extend_from_slice(scanline);
+
+		Ok(())
+	}
+
+	fn finish<W: Write>(&mut self, out: &mut W) -> io::Result<()> {
+		// end of block, then the rest of the last byte is padding
+		self.write_symbol(256);
+		self.write_bits(0, (8 - self.bit_count % 8) % 8);
+
+		let adler = (self.adler.1 << 16) | self.adler.0;
+		self.chunk.write_u32::<BigEndian>(adler)?;
+
+		write_chunk(out, b"IDAT", &self.chunk)?;
+		self.chunk.clear();
+
+		Ok(())
+	}
+
+	// deflate packs its bits starting at the lowest bit of every byte
+	fn write_bits(&mut self, value: u32, count: u32) {
+		self.bits |= (value as u64) << self.bit_count;
+		self.bit_count += count;
+
+		while self.bit_count >= 8 {
+			self.chunk.push(self.bits as u8);
+			self.bits >>= 8;
+			self.bit_count -= 8;
+		}
+	}
+
+	// Huffman codes are the exception, they go highest bit first
+	fn write_code(&mut self, code: u32, len: u32) {
+		self.write_bits(code.reverse_bits() >> (32 - len), len);
+	}
+
+	// a literal byte, the end of the block or a length code in the fixed literal/length alphabet
+	fn write_symbol(&mut self, symbol: u16) {
+		let symbol = symbol as u32;
+
+		match symbol {
+			0..=143 => self.write_code(0x30 + symbol, 8),
+			144..=255 => self.write_code(0x190 + symbol - 144, 9),
+			256..=279 => self.write_code(symbol - 256, 7),
+			_ => self.write_code(0xC0 + symbol - 280, 8)
+		}
+	}
+
+	fn write_match(&mut self, len: usize, distance: usize) {
+		let code = LENGTH_BASES.iter().rposition(|&base| base as usize <= len).unwrap();
+		self.write_symbol(257 + code as u16);
+		self.write_bits((len - LENGTH_BASES[code] as usize) as u32, LENGTH_EXTRA_BITS[code]);
+
+		let code = DISTANCE_BASES.iter().rposition(|&base| base as usize <= distance).unwrap();
+		self.write_code(code as u32, 5);
+		self.write_bits((distance - DISTANCE_BASES[code] as usize) as u32, DISTANCE_EXTRA_BITS[code]);
+	}
+}
@@ -0,0 +1,128 @@
+use std::io::{self, Write};
+use ::Grid;
+use ::utils::Direction::{S, E};
+use super::{RenderOptions, Path};
+
+// cells to mark as the way in and out of the maze
+pub struct Markers {
+	pub start: (u64, u64),
+	pub goal: (u64, u64)
+}
+
+fn colour(rgb: &[u8; 3]) -> String {
+	format!("#{:02x}{:02x}{:02x}", rgb[0], rgb[1], rgb[2])
+}
+
+// renders the grid as SVG using the same layout as the PNG renderer
+//
+// walls running along the same line are merged into a single segment, the grid is read row by
+// row keeping only the start of every vertical wall run that is still open
+pub fn write<G: Grid, W: Write>(grid: &G, path: Option<&Path>, markers: Option<&Markers>,
+	options: &RenderOptions, out: &mut W) -> io::Result<()> {
+	let (width, height) = (grid.width(), grid.height());
+	let cell_size = options.cell_size as u64;
+	let thickness = options.wall_thickness as u64;
+	// walls are drawn along the middle of the band they take up
+	let offset = thickness as f64 / 2.0;
+	let line = |x1: u64, y1: u64, x2: u64, y2: u64| format!(
+		"<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"/>\n",
+		(x1 * cell_size) as f64 + offset, (y1 * cell_size) as f64 + offset,
+		(x2 * cell_size) as f64 + offset, (y2 * cell_size) as f64 + offset);
+
+	write!(out, concat!("<svg xmlns=\"http://www.w3.org/2000/svg\" ",
+			"width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">\n"),
+		width * cell_size + thickness, height * cell_size + thickness)?;
+	writeln!(out, "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>",
+		colour(&options.background_color))?;
+	write!(out, concat!("<g id=\"walls\" stroke=\"{}\" stroke-width=\"{}\" ",
+			"stroke-linecap=\"square\">\n"),
+		colour(&options.wall_color), thickness)?;
+
+	// where the wall runs along the west side of each column started, if they are open
+	let mut vertical_runs: Vec<Option<u64>> = vec![None; width as usize + 1];
+
+	for y in 0..(height + 1) {
+		// the walls along the north side of this row
+		let mut run_start = None;
+
+		for x in 0..(width + 1) {
+			let wall = x < width
+				&& (y == 0 || y == height || grid.get(x, y - 1) & S as u8 == 0);
+
+			match (wall, run_start) {
+				(true, None) => run_start = Some(x),
+				(false, Some(start)) => {
+					out.write_all(line(start, y, x, y).as_bytes())?;
+					run_start = None;
+				},
+				_ => {}
+			}
+		}
+
+		// the walls along the west side of the cells in this row
+		for x in 0..(width + 1) {
+			let wall = y < height
+				&& (x == 0 || x == width || grid.get(x - 1, y) & E as u8 == 0);
+
+			match (wall, vertical_runs[x as usize]) {
+				(true, None) => vertical_runs[x as usize] = Some(y),
+				(false, Some(start)) => {
+					out.write_all(line(x, start, x, y).as_bytes())?;
+					vertical_runs[x as usize] = None;
+				},
+				_ => {}
+			}
+		}
+	}
+
+	out.write_all(b"</g>\n")?;
+
+	let centre = |(x, y): (u64, u64)| (
+		(x * cell_size) as f64 + offset + cell_size as f64 / 2.0,
+		(y * cell_size) as f64 + offset + cell_size as f64 / 2.0
+	);
+
+	if let Some(path) = path {
+		use ::utils::Direction::*;
+
+		write!(out, concat!("<g id=\"solution\" fill=\"none\" stroke=\"{}\" ",
+				"stroke-width=\"{}\" stroke-linecap=\"round\" stroke-linejoin=\"round\">\n"),
+			colour(&options.path_color), cell_size as f64 / 3.0)?;
+		out.write_all(b"<polyline points=\"")?;
+
+		let (mut x, mut y) = path.start;
+		let (cx, cy) = centre((x, y));
+		write!(out, "{},{}", cx, cy)?;
+
+		// only the cells where the path turns are needed
+		for (i, &dir) in path.directions.iter().enumerate() {
+			match dir {
+				S => y += 1,
+				E => x += 1,
+				N => y -= 1,
+				W => x -= 1
+			}
+
+			if path.directions.get(i + 1) != Some(&dir) {
+				let (cx, cy) = centre((x, y));
+				write!(out, " {},{}", cx, cy)?;
+			}
+		}
+
+		out.write_all(b"\"/>\n</g>\n")?;
+	}
+
+	if let Some(markers) = markers {
+		let radius = cell_size.saturating_sub(thickness) as f64 / 3.0;
+		let (sx, sy) = centre(markers.start);
+		let (gx, gy) = centre(markers.goal);
+
+		writeln!(out, "<g id=\"markers\" fill=\"{}\">", colour(&options.path_color))?;
+		writeln!(out, "<circle id=\"start\" cx=\"{}\" cy=\"{}\" r=\"{}\"/>", sx, sy, radius)?;
+		writeln!(out, "<rect id=\"goal\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/>",
+			gx - radius, gy - radius, radius * 2.0, radius * 2.0)?;
+		out.write_all(b"</g>\n")?;
+	}
+
+	out.write_all(b"</svg>\n")
+}
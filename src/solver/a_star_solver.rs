@@ -0,0 +1,159 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use ::{Grid, Solver, SolverOption, SolverSolution, PackedOption};
+use ::error::Result;
+use ::grid::in_memory_packed_grid::InMemoryPackedGrid;
+use ::utils::Direction;
+use super::utils::{Endpoints, trace_back};
+
+// estimates the remaining cost between two cells, it must never overestimate for the
+// returned path to be the cheapest one, it doesn't have to be consistent as a cell that was
+// expanded already is expanded again when a cheaper way to it turns up
+pub fn manhattan(from: (u64, u64), to: (u64, u64)) -> u64 {
+	from.0.abs_diff(to.0) + from.1.abs_diff(to.1)
+}
+
+#[derive(PartialEq, Eq)]
+struct Node {
+	estimate: u64,
+	cost: u64,
+	x: u64,
+	y: u64
+}
+
+impl Ord for Node {
+	// BinaryHeap is a max-heap, so the cheapest estimate has to compare as the greatest,
+	// ties are broken towards the node that got further already
+	fn cmp(&self, other: &Node) -> Ordering {
+		match other.estimate.cmp(&self.estimate) {
+			Ordering::Equal => self.cost.cmp(&other.cost),
+			ordering => ordering
+		}
+	}
+}
+
+impl PartialOrd for Node {
+	fn partial_cmp(&self, other: &Node) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+type Heuristic<'a> = Box<dyn Fn((u64, u64), (u64, u64)) -> u64 + 'a>;
+type Cost<'a> = Box<dyn Fn(u64, u64) -> u64 + 'a>;
+
+pub struct AStarSolver<'a, G: 'a + Grid> {
+	grid: &'a G,
+	endpoints: Endpoints,
+	heuristic: Heuristic<'a>,
+	cost: Option<Cost<'a>>
+}
+
+impl<'a, G: 'a + Grid> AStarSolver<'a, G> {
+	pub fn new(grid: &'a G, options: &[SolverOption]) -> Result<Self> {
+		Ok(AStarSolver {
+			grid,
+			endpoints: Endpoints::new(grid, options)?,
+			heuristic: Box::new(manhattan),
+			cost: None
+		})
+	}
+
+	pub fn heuristic<H>(mut self, heuristic: H) -> Self
+		where H: Fn((u64, u64), (u64, u64)) -> u64 + 'a {
+		self.heuristic = Box::new(heuristic);
+		self
+	}
+
+	// the cost of stepping into a cell, every step costs 1 when not given
+	pub fn cost<C>(mut self, cost: C) -> Self
+		where C: Fn(u64, u64) -> u64 + 'a {
+		self.cost = Some(Box::new(cost));
+		self
+	}
+
+	fn step_cost(&self, x: u64, y: u64) -> u64 {
+		match self.cost {
+			Some(ref cost) => cost(x, y),
+			None => 1
+		}
+	}
+
+	// the estimate towards the closest of the goals
+	fn estimate(&self, x: u64, y: u64) -> u64 {
+		self.endpoints.goals.iter()
+			.map(|&goal| (self.heuristic)((x, y), goal))
+			.min()
+			.unwrap_or(0)
+	}
+
+	fn solve_at(&self, sx: u64, sy: u64) -> Option<SolverSolution> {
+		use ::utils::Direction::*;
+
+		let (width, height) = (self.grid.width(), self.grid.height());
+
+		// the cheapest known cost of reaching each cell seen so far, kept after a cell is expanded
+		// so it's only expanded again for a cheaper cost
+		let mut costs = HashMap::new();
+		// the direction each reached cell was entered from, as an index into Direction::enumerate()
+		let mut parents = InMemoryPackedGrid::new(&[
+			PackedOption::Width(width),
+			PackedOption::Height(height)
+		]).expect("the parents have the same dimensions as the grid");
+		let mut open = BinaryHeap::new();
+
+		costs.insert(sy * width + sx, 0);
+		open.push(Node { estimate: self.estimate(sx, sy), cost: 0, x: sx, y: sy });
+
+		while let Some(Node { cost, x, y, .. }) = open.pop() {
+			let index = y * width + x;
+
+			// a cheaper way to this cell has been found since this node was queued
+			if cost > costs[&index] {
+				continue;
+			}
+
+			if self.endpoints.is_goal(x, y) {
+				return Some(trace_back(&parents, (sx, sy), (x, y)));
+			}
+
+			for (i, &dir) in Direction::enumerate().iter().enumerate() {
+				if self.grid.test(x, y, dir as u8) {
+					let (nx, ny) = match dir {
+						S => (x, y + 1),
+						E => (x + 1, y),
+						N => (x, y - 1),
+						W => (x - 1, y)
+					};
+					let next_index = ny * width + nx;
+
+					if nx >= width || ny >= height {
+						continue;
+					}
+
+					let next_cost = cost + self.step_cost(nx, ny);
+
+					if costs.get(&next_index).is_none_or(|&known| next_cost < known) {
+						costs.insert(next_index, next_cost);
+						parents.set(nx, ny, i as u8);
+						open.push(Node {
+							estimate: next_cost + self.estimate(nx, ny),
+							cost: next_cost,
+							x: nx,
+							y: ny
+						});
+					}
+				}
+			}
+		}
+
+		None
+	}
+}
+
+impl<'a, G: 'a + Grid> Solver for AStarSolver<'a, G> {
+	fn solve(self) -> Option<SolverSolution> {
+		let (sx, sy) = self.endpoints.start;
+
+		self.solve_at(sx, sy)
+	}
+}
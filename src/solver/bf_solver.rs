@@ -0,0 +1,71 @@
+use std::collections::VecDeque;
+use ::{Grid, Solver, SolverOption, SolverSolution, PackedOption};
+use ::error::Result;
+use ::bitset::BitSet;
+use ::grid::in_memory_packed_grid::InMemoryPackedGrid;
+use ::utils::Direction;
+use super::utils::{Endpoints, trace_back};
+
+pub struct BFSolver<'a, G: 'a + Grid> {
+	grid: &'a G,
+	endpoints: Endpoints
+}
+
+impl<'a, G: 'a + Grid> BFSolver<'a, G> {
+	pub fn new(grid: &'a G, options: &[SolverOption]) -> Result<Self> {
+		Ok(BFSolver {
+			grid,
+			endpoints: Endpoints::new(grid, options)?
+		})
+	}
+
+	fn solve_at(&self, sx: u64, sy: u64) -> Option<SolverSolution> {
+		use ::utils::Direction::*;
+
+		let (width, height) = (self.grid.width(), self.grid.height());
+
+		let mut visited = BitSet::new(width * height);
+		// the direction each visited cell was entered from, as an index into Direction::enumerate()
+		let mut parents = InMemoryPackedGrid::new(&[
+			PackedOption::Width(width),
+			PackedOption::Height(height)
+		]).expect("the parents have the same dimensions as the grid");
+		let mut queue = VecDeque::new();
+
+		visited.insert(sy * width + sx);
+		queue.push_back((sx, sy));
+
+		while let Some((x, y)) = queue.pop_front() {
+			if self.endpoints.is_goal(x, y) {
+				return Some(trace_back(&parents, (sx, sy), (x, y)));
+			}
+
+			for (i, &dir) in Direction::enumerate().iter().enumerate() {
+				if self.grid.test(x, y, dir as u8) {
+					let (nx, ny) = match dir {
+						S => (x, y + 1),
+						E => (x + 1, y),
+						N => (x, y - 1),
+						W => (x - 1, y)
+					};
+
+					if nx < width && ny < height && !visited.contains(ny * width + nx) {
+						visited.insert(ny * width + nx);
+						parents.set(nx, ny, i as u8);
+						queue.push_back((nx, ny));
+					}
+				}
+			}
+		}
+
+		None
+	}
+}
+
+impl<'a, G: 'a + Grid> Solver for BFSolver<'a, G> {
+	fn solve(self) -> Option<SolverSolution> {
+		let (sx, sy) = self.endpoints.start;
+
+		self.solve_at(sx, sy)
+	}
+}
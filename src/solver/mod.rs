@@ -1,2 +1,77 @@
 pub mod recursive_df_solver;
 pub mod stack_df_solver;
+pub mod bf_solver;
+pub mod a_star_solver;
+
+mod utils {
+	use std::collections::HashSet;
+	use ::{Grid, SolverOption, SolverSolution};
+	use ::error::{Error, Result};
+	use ::utils::Direction;
+
+	// where a solver starts and where it may stop, the top left and bottom right corners
+	// unless given otherwise
+	pub struct Endpoints {
+		pub start: (u64, u64),
+		pub goals: HashSet<(u64, u64)>
+	}
+
+	impl Endpoints {
+		pub fn new<G: Grid>(grid: &G, options: &[SolverOption]) -> Result<Self> {
+			let mut start = (0, 0);
+			let mut goals = HashSet::new();
+
+			for o in options {
+				match *o {
+					SolverOption::Start(x, y) => start = (x, y),
+					SolverOption::Goal(x, y) => { goals.insert((x, y)); }
+				}
+			}
+
+			if goals.is_empty() {
+				goals.insert((grid.width() - 1, grid.height() - 1));
+			}
+
+			for &(x, y) in Some(&start).into_iter().chain(goals.iter()) {
+				if x >= grid.width() || y >= grid.height() {
+					return Err(Error::InvalidArgument(format!("cell {},{} is outside of the {}x{} maze",
+						x, y, grid.width(), grid.height())));
+				}
+			}
+
+			Ok(Endpoints {
+				start,
+				goals
+			})
+		}
+
+		#[inline]
+		pub fn is_goal(&self, x: u64, y: u64) -> bool {
+			self.goals.contains(&(x, y))
+		}
+	}
+
+	// walks the recorded directions back from the goal to the start
+	pub fn trace_back<P: Grid>(parents: &P, start: (u64, u64), goal: (u64, u64)) -> SolverSolution {
+		use ::utils::Direction::*;
+
+		let mut path = SolverSolution::new();
+		let (mut x, mut y) = goal;
+
+		while (x, y) != start {
+			let dir = Direction::enumerate()[parents.get(x, y) as usize];
+
+			match dir {
+				S => y -= 1,
+				E => x -= 1,
+				N => y += 1,
+				W => x += 1
+			}
+
+			path.push(dir);
+		}
+
+		path.reverse();
+		path
+	}
+}
@@ -1,63 +1,71 @@
-use ::{Grid, Solver, SolverSolution};
+use ::{Grid, Solver, SolverOption, SolverSolution};
+use ::bitset::BitSet;
+use ::error::Result;
 use ::utils::Direction;
+use super::utils::Endpoints;
 
+// Explores the whole maze depth first and returns the path to the nearest goal, which in a
+// maze with loops isn't necessarily the shortest one, BFSolver and AStarSolver find those.
 pub struct RecursiveDFSolver<'a, G: 'a + Grid> {
 	grid: &'a G,
-	path: SolverSolution
+	endpoints: Endpoints,
+	path: SolverSolution,
+	visited: BitSet,
+	nearest: Option<SolverSolution>
 }
 
 impl<'a, G: 'a + Grid> RecursiveDFSolver<'a, G> {
-	pub fn new(grid: &'a G) -> Self {
-		RecursiveDFSolver {
-			grid: grid,
-			path: SolverSolution::new()
-		}
+	pub fn new(grid: &'a G, options: &[SolverOption]) -> Result<Self> {
+		Ok(RecursiveDFSolver {
+			grid,
+			endpoints: Endpoints::new(grid, options)?,
+			path: SolverSolution::new(),
+			visited: BitSet::new(grid.width() * grid.height()),
+			nearest: None
+		})
 	}
 
-	fn solve_at(&mut self, x: i64, y: i64) -> bool {
+	fn solve_at(&mut self, x: u64, y: u64) {
 		use ::utils::Direction::*;
 
-		if x as u64 == self.grid.width() - 1 && y as u64 == self.grid.height() - 1 {
-			true
-		} else if x < 0 || x as u64 >= self.grid.width()
-			|| y < 0 || y as u64 >= self.grid.height() {
-			false
-		} else {
-			for &dir in Direction::enumerate() {
-				if dir != self.path.last().unwrap_or(&S).opposite()
-					&& self.grid.test(x as u64, y as u64, dir as u8) {
-
-					let (dx, dy) = match dir {
-						S => (0, 1),
-						E => (1, 0),
-						N => (0, -1),
-						W => (-1, 0)
-					};
+		let (width, height) = (self.grid.width(), self.grid.height());
+
+		self.visited.insert(y * width + x);
 
+		if self.endpoints.is_goal(x, y) {
+			// only paths shorter than the nearest goal so far are followed
+			self.nearest = Some(self.path.clone());
+			return;
+		}
+
+		for &dir in Direction::enumerate() {
+			if self.nearest.as_ref().is_some_and(|nearest| self.path.len() + 1 >= nearest.len()) {
+				return;
+			}
+
+			if self.grid.test(x, y, dir as u8) {
+				let (nx, ny) = match dir {
+					S => (x, y + 1),
+					E => (x + 1, y),
+					N => (x, y - 1),
+					W => (x - 1, y)
+				};
+
+				if nx < width && ny < height && !self.visited.contains(ny * width + nx) {
 					self.path.push(dir);
-					
-					let has_path = self.solve_at(x + dx, y + dy);
-					if has_path {
-						return true;
-					}
-					
+					self.solve_at(nx, ny);
 					self.path.pop();
 				}
 			}
-
-			false
 		}
 	}
 }
 
 impl<'a, G: 'a + Grid> Solver for RecursiveDFSolver<'a, G> {
 	fn solve(mut self) -> Option<SolverSolution> {
-		let has_path = self.solve_at(0, 0);
+		let (sx, sy) = self.endpoints.start;
 
-		if has_path {
-			Some(self.path)
-		} else {
-			None
-		}
+		self.solve_at(sx, sy);
+		self.nearest
 	}
 }
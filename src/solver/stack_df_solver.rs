@@ -1,70 +1,77 @@
-use ::{Grid, Solver, SolverSolution};
+use ::{Grid, Solver, SolverOption, SolverSolution};
+use ::bitset::BitSet;
+use ::error::Result;
 use ::utils::Direction;
+use super::utils::Endpoints;
 
+// Explores the whole maze depth first and returns the path to the nearest goal, which in a
+// maze with loops isn't necessarily the shortest one, BFSolver and AStarSolver find those.
 pub struct StackDFSolver<'a, G: 'a + Grid> {
 	grid: &'a G,
+	endpoints: Endpoints,
 	path: SolverSolution
 }
 
 impl<'a, G: 'a + Grid> StackDFSolver<'a, G> {
-	pub fn new(grid: &'a G) -> Self {
-		StackDFSolver {
-			grid: grid,
+	pub fn new(grid: &'a G, options: &[SolverOption]) -> Result<Self> {
+		Ok(StackDFSolver {
+			grid,
+			endpoints: Endpoints::new(grid, options)?,
 			path: SolverSolution::new()
-		}
+		})
 	}
 
-	fn solve_at(&mut self, x: u64, y: u64) -> bool {
+	fn solve_at(&mut self, x: u64, y: u64) -> Option<SolverSolution> {
 		use ::utils::Direction::*;
 
-		let mut stack = Vec::new();
+		let (width, height) = (self.grid.width(), self.grid.height());
 
-		stack.push((x as i64, y as i64, (0,)));
+		let mut visited = BitSet::new(width * height);
+		let mut nearest: Option<SolverSolution> = None;
+		let mut stack = Vec::new();
 
-		'stack_loop: while !stack.is_empty() {
-			let (x, y, (i,)) = stack.pop().unwrap();
+		visited.insert(y * width + x);
+		stack.push((x, y, (0,)));
 
-			if x as u64 == self.grid.width() - 1 && y as u64 == self.grid.height() - 1 {
-				return true
-			} else if x >= 0 && (x as u64) < self.grid.width()
-				&& y >= 0 && (y as u64) < self.grid.height() {
+		'stack_loop: while let Some((x, y, (i,))) = stack.pop() {
+			if i == 0 && self.endpoints.is_goal(x, y) {
+				// only paths shorter than the nearest goal so far are followed
+				nearest = Some(self.path.clone());
+			} else if nearest.as_ref().is_none_or(|nearest| self.path.len() + 1 < nearest.len()) {
 				for i in i..Direction::enumerate().len() {
 					let dir = Direction::enumerate()[i];
 
-					if dir != self.path.last().unwrap_or(&S).opposite()
-						&& self.grid.test(x as u64, y as u64, dir as u8) {
-						
-						let (dx, dy) = match dir {
-							S => (0, 1),
-							E => (1, 0),
-							N => (0, -1),
-							W => (-1, 0)
+					if self.grid.test(x, y, dir as u8) {
+						let (nx, ny) = match dir {
+							S => (x, y + 1),
+							E => (x + 1, y),
+							N => (x, y - 1),
+							W => (x - 1, y)
 						};
 
-						self.path.push(dir);
+						if nx < width && ny < height && !visited.contains(ny * width + nx) {
+							visited.insert(ny * width + nx);
+							self.path.push(dir);
 
-						stack.push((x, y, (i + 1,)));
-						stack.push((x + dx, y + dy, (0,)));
-						continue 'stack_loop;
+							stack.push((x, y, (i + 1,)));
+							stack.push((nx, ny, (0,)));
+							continue 'stack_loop;
+						}
 					}
 				}
 			}
-			
+
 			self.path.pop();
 		}
 
-		false		
+		nearest
 	}
 }
 
 impl<'a, G: 'a + Grid> Solver for StackDFSolver<'a, G> {
 	fn solve(mut self) -> Option<SolverSolution> {
-		let has_path = self.solve_at(0, 0);
+		let (sx, sy) = self.endpoints.start;
 
-		if has_path {
-			Some(self.path)
-		} else {
-			None
-		}
+		self.solve_at(sx, sy)
 	}
 }
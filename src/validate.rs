@@ -0,0 +1,199 @@
+use std::fmt;
+use ::Grid;
+use ::utils::Direction::{S, E};
+
+#[derive(Clone, Debug)]
+pub struct Report {
+	pub width: u64,
+	pub height: u64,
+
+	// number of connected regions, a perfect maze has exactly one
+	pub regions: u64,
+	// regions that can't be reached from the top left cell, with a cell of the first one found
+	pub unreachable_regions: u64,
+	pub first_unreachable: Option<(u64, u64)>,
+	// passages that close a loop, with the cell of the first one found
+	pub cycles: u64,
+	pub first_cycle: Option<(u64, u64)>,
+	// passages leading out of the grid through the south or east border
+	pub border_passages: u64,
+	pub first_border_passage: Option<(u64, u64)>
+}
+
+impl Report {
+	pub fn is_perfect(&self) -> bool {
+		self.regions == 1 && self.cycles == 0 && self.border_passages == 0
+	}
+}
+
+impl fmt::Display for Report {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		writeln!(f, "size: {}x{}", self.width, self.height)?;
+		writeln!(f, "regions: {}", self.regions)?;
+		writeln!(f, "unreachable regions: {}{}",
+			self.unreachable_regions, format_cell(self.first_unreachable))?;
+		writeln!(f, "cycles: {}{}", self.cycles, format_cell(self.first_cycle))?;
+		writeln!(f, "border passages: {}{}",
+			self.border_passages, format_cell(self.first_border_passage))?;
+		write!(f, "perfect: {}", if self.is_perfect() { "yes" } else { "no" })
+	}
+}
+
+fn format_cell(cell: Option<(u64, u64)>) -> String {
+	match cell {
+		Some((x, y)) => format!(" (first at {},{})", x, y),
+		None => String::new()
+	}
+}
+
+// checks whether the grid holds a perfect maze, i.e. a spanning tree of its cells
+//
+// the grid is walked row by row keeping track of which cells of the current row are connected,
+// so only O(width) memory is used no matter how tall the grid is
+pub fn validate<G: Grid>(grid: &G) -> Report {
+	let (width, height) = (grid.width(), grid.height());
+	let w = width as usize;
+
+	let mut report = Report {
+		width,
+		height,
+		regions: 0,
+		unreachable_regions: 0,
+		first_unreachable: None,
+		cycles: 0,
+		first_cycle: None,
+		border_passages: 0,
+		first_border_passage: None
+	};
+
+	// the region every cell of the previous row belongs to, region labels are always < width
+	let mut labels = vec![0usize; w];
+	let mut label_count = 0;
+	// a cell of each region, to be able to point at it
+	let mut representatives = vec![(0u64, 0u64); w];
+	// the region of the top left cell, None once the region is closed off
+	let mut start_label = None;
+
+	// nodes 0..w are the regions of the previous row, nodes w..2w are the cells of the current one
+	let mut parent = vec![0usize; 2 * w];
+	let mut alive = vec![false; 2 * w];
+	let mut relabel = vec![0usize; 2 * w];
+	let mut next_representatives = vec![(0u64, 0u64); w];
+
+	for y in 0..height {
+		for node in 0..(2 * w) {
+			parent[node] = node;
+			alive[node] = false;
+		}
+
+		for x in 0..width {
+			let value = grid.get(x, y);
+
+			if y > 0 && grid.get(x, y - 1) & S as u8 != 0
+				&& !union(&mut parent, labels[x as usize], w + x as usize) {
+				report.cycles += 1;
+				report.first_cycle = report.first_cycle.or(Some((x, y - 1)));
+			}
+
+			if value & E as u8 != 0 {
+				if x + 1 == width {
+					report.border_passages += 1;
+					report.first_border_passage = report.first_border_passage.or(Some((x, y)));
+				} else if !union(&mut parent, w + x as usize, w + x as usize + 1) {
+					report.cycles += 1;
+					report.first_cycle = report.first_cycle.or(Some((x, y)));
+				}
+			}
+
+			if value & S as u8 != 0 && y + 1 == height {
+				report.border_passages += 1;
+				report.first_border_passage = report.first_border_passage.or(Some((x, y)));
+			}
+		}
+
+		for x in 0..w {
+			let root = find(&mut parent, w + x);
+			alive[root] = true;
+		}
+
+		// regions of the previous row that don't continue on this one are complete
+		for (label, &representative) in representatives.iter().enumerate().take(label_count) {
+			let root = find(&mut parent, label);
+
+			if !alive[root] {
+				close_region(&mut report, start_label == Some(label), representative);
+			}
+		}
+
+		if let Some(label) = start_label {
+			let root = find(&mut parent, label);
+
+			if !alive[root] {
+				start_label = None;
+			}
+		}
+
+		// compact the regions of this row into labels 0..label_count
+		for slot in relabel.iter_mut().take(2 * w) {
+			*slot = w;
+		}
+
+		label_count = 0;
+
+		for (x, label) in labels.iter_mut().enumerate().take(w) {
+			let root = find(&mut parent, w + x);
+
+			if relabel[root] == w {
+				relabel[root] = label_count;
+				next_representatives[label_count] = (x as u64, y);
+				label_count += 1;
+			}
+
+			*label = relabel[root];
+		}
+
+		start_label = if y == 0 {
+			Some(labels[0])
+		} else {
+			start_label.map(|label| relabel[find(&mut parent, label)])
+		};
+
+		representatives[..label_count].copy_from_slice(&next_representatives[..label_count]);
+	}
+
+	for (label, &representative) in representatives.iter().enumerate().take(label_count) {
+		close_region(&mut report, start_label == Some(label), representative);
+	}
+
+	report
+}
+
+fn close_region(report: &mut Report, is_start: bool, representative: (u64, u64)) {
+	report.regions += 1;
+
+	if !is_start {
+		report.unreachable_regions += 1;
+		report.first_unreachable = report.first_unreachable.or(Some(representative));
+	}
+}
+
+fn find(parent: &mut [usize], mut node: usize) -> usize {
+	while parent[node] != node {
+		parent[node] = parent[parent[node]];
+		node = parent[node];
+	}
+
+	node
+}
+
+// joins the sets of two nodes, returns false if they were already joined
+fn union(parent: &mut [usize], a: usize, b: usize) -> bool {
+	let (a, b) = (find(parent, a), find(parent, b));
+
+	if a == b {
+		false
+	} else {
+		parent[b] = a;
+		true
+	}
+}
@@ -0,0 +1,33 @@
+extern crate maze;
+
+use maze::{GeneratorType, GeneratorOption, PackedOption, InMemoryPackedGrid, generate};
+use maze::generator::Seed;
+use maze::analysis::analyze;
+
+fn dead_ends(generator_type: GeneratorType, seed: u64, braid: Option<f64>) -> u64 {
+	let mut grid = InMemoryPackedGrid::new(&[PackedOption::Width(100), PackedOption::Height(100)])
+		.unwrap();
+	let mut options = vec![GeneratorOption::Seed(Seed::from(seed))];
+	options.extend(braid.map(GeneratorOption::Braid));
+
+	generate(&mut grid, generator_type, &options).unwrap();
+
+	analyze(&grid, None).dead_ends
+}
+
+#[test]
+fn braid_removes_the_given_fraction_of_dead_ends() {
+	for &generator_type in &[GeneratorType::Prims, GeneratorType::Sidewinder] {
+		for seed in 0..5 {
+			let before = dead_ends(generator_type, seed, None);
+
+			for &fraction in &[0.0, 0.25, 0.5, 1.0] {
+				let after = dead_ends(generator_type, seed, Some(fraction));
+				let removed = (before - after) as f64 / before as f64;
+
+				assert!((removed - fraction).abs() < 0.01,
+					"{} of the dead ends removed instead of {}", removed, fraction);
+			}
+		}
+	}
+}
@@ -0,0 +1,87 @@
+extern crate maze;
+
+use std::env;
+use std::fs::{self, OpenOptions};
+use maze::{Grid, GeneratorType, GeneratorOption, PackedOption, InMemoryPackedGrid, MMAPPackedGrid,
+	Error, generate};
+use maze::generator::{Progress, Seed};
+use maze::validate::validate;
+
+const WIDTH: u64 = 61;
+const HEIGHT: u64 = 47;
+const INTERVAL: u64 = 50;
+
+fn cells<G: Grid>(grid: &G) -> Vec<u8> {
+	let mut cells = Vec::new();
+
+	for y in 0..grid.height() {
+		for x in 0..grid.width() {
+			cells.push(grid.get(x, y));
+		}
+	}
+
+	cells
+}
+
+// cancels the generation once `cancel_at` cells are done, resumes it from the checkpoint in a
+// reopened grid and compares the result with a generation that wasn't interrupted
+fn resume(generator_type: GeneratorType, name: &str, cancel_at: u64) {
+	let dir = env::temp_dir();
+	let grid_path = dir.join(format!("maze_test_{}_{}.grid", name, cancel_at));
+	let checkpoint_path = dir.join(format!("maze_test_{}_{}.checkpoint", name, cancel_at));
+	let checkpoint_path = checkpoint_path.to_str().unwrap();
+	let seed = GeneratorOption::Seed(Seed::from(7u64));
+
+	let _ = fs::remove_file(checkpoint_path);
+
+	let mut grid = MMAPPackedGrid::new(&[
+		PackedOption::MMAPFilePath(grid_path.to_str().unwrap().to_string()),
+		PackedOption::Width(WIDTH),
+		PackedOption::Height(HEIGHT)
+	]).unwrap();
+
+	let result = generate(&mut grid, generator_type, &[
+		seed.clone(),
+		GeneratorOption::Checkpoint(checkpoint_path.to_string(), INTERVAL),
+		GeneratorOption::Progress(Progress::new(1, move |done, _| done < cancel_at))
+	]);
+
+	assert!(matches!(result, Err(Error::Cancelled)));
+	assert!(fs::metadata(checkpoint_path).is_ok());
+
+	drop(grid);
+
+	let file = OpenOptions::new().read(true).write(true).open(&grid_path).unwrap();
+	let mut grid = MMAPPackedGrid::from_file(file).unwrap();
+
+	generate(&mut grid, generator_type, &[
+		seed.clone(),
+		GeneratorOption::Checkpoint(checkpoint_path.to_string(), INTERVAL)
+	]).unwrap();
+
+	let mut uninterrupted = InMemoryPackedGrid::new(&[
+		PackedOption::Width(WIDTH),
+		PackedOption::Height(HEIGHT)
+	]).unwrap();
+
+	generate(&mut uninterrupted, generator_type, &[seed]).unwrap();
+
+	assert_eq!(cells(&grid), cells(&uninterrupted));
+	assert!(validate(&grid).is_perfect());
+	assert!(fs::metadata(checkpoint_path).is_err());
+
+	drop(grid);
+	let _ = fs::remove_file(&grid_path);
+}
+
+#[test]
+fn stack_backtrack_resumes_from_a_checkpoint() {
+	resume(GeneratorType::StackBacktrack, "stack_backtrack", INTERVAL * 10);
+	resume(GeneratorType::StackBacktrack, "stack_backtrack", INTERVAL * 10 + 17);
+}
+
+#[test]
+fn ellers_resumes_from_a_checkpoint() {
+	resume(GeneratorType::EllersAlgorithm, "ellers", INTERVAL * 10);
+	resume(GeneratorType::EllersAlgorithm, "ellers", INTERVAL * 10 + 17);
+}
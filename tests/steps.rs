@@ -0,0 +1,53 @@
+extern crate maze;
+
+use maze::{Grid, GeneratorType, GeneratorOption, PackedOption, InMemoryPackedGrid, generate};
+use maze::generator::{Steps, Step, Seed};
+use maze::Direction::{S, E};
+
+fn new_grid() -> InMemoryPackedGrid {
+	InMemoryPackedGrid::new(&[PackedOption::Width(5), PackedOption::Height(4)]).unwrap()
+}
+
+fn cells<G: Grid>(grid: &G) -> Vec<u8> {
+	let mut cells = Vec::new();
+
+	for y in 0..grid.height() {
+		for x in 0..grid.width() {
+			cells.push(grid.get(x, y));
+		}
+	}
+
+	cells
+}
+
+#[test]
+fn steps_replay_to_the_generated_grid() {
+	let options = [GeneratorOption::Seed(Seed::from(1u64))];
+
+	let mut steps = Steps::new(new_grid(), GeneratorType::Prims, &options);
+	let recorded = steps.by_ref().collect::<Vec<_>>();
+	let stepped = steps.into_grid().unwrap();
+
+	assert_eq!(&recorded[..6], &[
+		Step::Carve(2, 1, S), Step::Carve(2, 2, S), Step::Carve(1, 3, E),
+		Step::Carve(2, 1, E), Step::Carve(2, 3, E), Step::Carve(3, 3, E)
+	]);
+
+	let mut replayed = new_grid();
+
+	for &step in &recorded {
+		match step {
+			Step::Carve(x, y, dir) => replayed.or_set(x, y, dir as u8),
+			Step::Wall(x, y, dir) => replayed.unset_provided(x, y, dir as u8),
+			Step::Fill(fill) => replayed.fill(fill)
+		}
+	}
+
+	let mut generated = new_grid();
+	generate(&mut generated, GeneratorType::Prims, &options).unwrap();
+
+	// a perfect maze of 20 cells has 19 passages, each carved once
+	assert_eq!(recorded.len(), 19);
+	assert_eq!(cells(&replayed), cells(&generated));
+	assert_eq!(cells(&stepped), cells(&generated));
+}